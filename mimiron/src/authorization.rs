@@ -39,6 +39,10 @@ pub fn set_blizzard_client_auth(
     _ = BLIZZARD_CLIENT_AUTH.write().insert((id, secret));
 }
 
+pub(crate) fn has_blizzard_client_auth() -> bool {
+    BLIZZARD_CLIENT_AUTH.read().is_some()
+}
+
 fn internal_get_access_token() -> Result<AccessToken> {
     let (id, secret) = BLIZZARD_CLIENT_AUTH.read().clone().unwrap_or_else(|| {
         panic!(
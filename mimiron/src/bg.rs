@@ -17,6 +17,8 @@ use std::{
 };
 use unicode_width::UnicodeWidthStr;
 
+pub use crate::bg_meta::{CompStats, HeroStats, comp_stats, hero_stats};
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct CardData {
@@ -0,0 +1,330 @@
+use crate::{
+    bg::Pool,
+    card_details::MinionType,
+    hearth_sim::get_hearth_sim_dbf_ids,
+    localization::{Locale, Localize},
+    meta::{casify_archetype, get_firestone_json},
+    meta_cards::percent,
+};
+use anyhow::Result;
+use compact_str::CompactString;
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+    ops::Not,
+    sync::Arc,
+    time::Duration,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FirestoneHeroStats {
+    hero_stats: Vec<HeroStat>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct HeroStat {
+    hero_card_id: CompactString,
+    data_points: u32,
+    average_position: f64,
+    total_offered: u32,
+    total_picked: u32,
+    placement_distribution: Vec<Placement>,
+    tribe_stats: Vec<TribeStat>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Placement {
+    rank: u8,
+    total_matches: u32,
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct TribeStat {
+    // Blizzard's minion type id.
+    tribe: u8,
+    // How much the hero's average position changes when the tribe is in the lobby. Lower is better.
+    impact_average_position: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FirestoneCompStats {
+    comp_stats: Vec<CompStat>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct CompStat {
+    // A slug, such as `murloc-brann`.
+    #[serde(alias = "archetypeId")]
+    comp_id: CompactString,
+    data_points: u32,
+    average_placement: f64,
+    placement_distribution: Vec<Placement>,
+}
+
+// Matches placed in the top 4, and all matches.
+fn top4(placements: &[Placement]) -> (u32, u32) {
+    placements.iter().fold((0, 0), |(top4, total), p| {
+        let top4 = if p.rank <= 4 {
+            top4 + p.total_matches
+        } else {
+            top4
+        };
+        (top4, total + p.total_matches)
+    })
+}
+
+fn rate(
+    part: u32,
+    total: u32,
+) -> Option<f64> {
+    (total > 0).then(|| f64::from(part) / f64::from(total))
+}
+
+/// How a Battlegrounds hero does in the last patch, over all ratings.
+#[derive(Clone, PartialEq)]
+pub struct HeroStats {
+    pub games: u32,
+    pub average_position: f64,
+    /// How often the hero is picked when offered. `None` when Firestone has no offers counted.
+    pub pick_rate: Option<f64>,
+    pub top4_rate: Option<f64>,
+    /// Tribes that lower the hero's average position the most when in the lobby, best first.
+    pub tribes: Vec<MinionType>,
+}
+
+impl From<&HeroStat> for HeroStats {
+    fn from(stat: &HeroStat) -> Self {
+        let (top4, total) = top4(&stat.placement_distribution);
+
+        Self {
+            games: stat.data_points,
+            average_position: stat.average_position,
+            pick_rate: rate(stat.total_picked, stat.total_offered),
+            top4_rate: rate(top4, total),
+            tribes: stat
+                .tribe_stats
+                .iter()
+                .filter(|t| t.impact_average_position < 0.0)
+                .sorted_by(|t1, t2| {
+                    t1.impact_average_position
+                        .total_cmp(&t2.impact_average_position)
+                })
+                .filter_map(|t| MinionType::try_from(t.tribe).ok())
+                .take(3)
+                .collect(),
+        }
+    }
+}
+
+impl Localize for HeroStats {
+    fn in_locale(
+        &self,
+        locale: Locale,
+    ) -> impl Display {
+        let mut ret = format!(
+            "{:.2} avg place, {} top 4, {} picked",
+            self.average_position,
+            percent(self.top4_rate),
+            percent(self.pick_rate)
+        );
+        if self.tribes.is_empty().not() {
+            ret.push_str(", best with ");
+            ret.push_str(
+                &self
+                    .tribes
+                    .iter()
+                    .map(|t| t.in_locale(locale).to_string())
+                    .join("/"),
+            );
+        }
+        write!(ret, " ({} games)", self.games).ok();
+
+        ret
+    }
+}
+
+/// How a Battlegrounds composition does in the last patch, over all ratings.
+#[derive(Clone, PartialEq)]
+pub struct CompStats {
+    pub name: CompactString,
+    pub games: u32,
+    pub average_position: f64,
+    pub top4_rate: Option<f64>,
+}
+
+impl From<&CompStat> for CompStats {
+    fn from(stat: &CompStat) -> Self {
+        let (top4, total) = top4(&stat.placement_distribution);
+
+        Self {
+            name: casify_archetype(&stat.comp_id),
+            games: stat.data_points,
+            average_position: stat.average_placement,
+            top4_rate: rate(top4, total),
+        }
+    }
+}
+
+impl Localize for CompStats {
+    fn in_locale(
+        &self,
+        _: Locale,
+    ) -> impl Display {
+        format!(
+            "{}: {:.2} avg place, {} top 4 ({} games)",
+            self.name,
+            self.average_position,
+            percent(self.top4_rate),
+            self.games
+        )
+    }
+}
+
+// Compositions with fewer games are too noisy to rank.
+const MIN_COMP_GAMES: u32 = 100;
+
+const fn mode(pool: Pool) -> &'static str {
+    match pool {
+        Pool::All | Pool::Solos => "",
+        Pool::Duos => "duos/",
+    }
+}
+
+/// Stats of every Battlegrounds hero in the pool's mode, by card id. `Pool::All` gives Solos
+/// stats.
+pub fn hero_stats(pool: Pool) -> Result<Arc<HashMap<usize, HeroStats>>> {
+    get_hero_stats(format!(
+        "https://static.zerotoheroes.com/api/bgs/{}hero-stats/mmr-100/last-patch/overview-from-hourly.gz.json",
+        mode(pool)
+    ))
+}
+
+#[cached::proc_macro::cached(
+    time = 86400, // one day.
+    result = true,
+)]
+fn get_hero_stats(link: String) -> Result<Arc<HashMap<usize, HeroStats>>> {
+    let stats = get_firestone_json::<FirestoneHeroStats>(&link)?;
+    let ids = get_hearth_sim_dbf_ids(stats.hero_stats.iter().map(|s| s.hero_card_id.as_str()));
+
+    Ok(Arc::new(
+        stats
+            .hero_stats
+            .iter()
+            .filter_map(|stat| {
+                let id = *ids.get(stat.hero_card_id.as_str())?;
+                Some((id, HeroStats::from(stat)))
+            })
+            .collect(),
+    ))
+}
+
+/// The dominant Battlegrounds compositions in the pool's mode, best average position first.
+/// `Pool::All` gives Solos stats.
+pub fn comp_stats(pool: Pool) -> Result<Arc<[CompStats]>> {
+    get_comp_stats(format!(
+        "https://static.zerotoheroes.com/api/bgs/{}comp-stats/mmr-100/last-patch/overview-from-hourly.gz.json",
+        mode(pool)
+    ))
+}
+
+#[cached::proc_macro::cached(
+    time = 86400, // one day.
+    result = true,
+)]
+fn get_comp_stats(link: String) -> Result<Arc<[CompStats]>> {
+    let stats = get_firestone_json::<FirestoneCompStats>(&link)?;
+    Ok(ranked_comps(&stats.comp_stats).into())
+}
+
+fn ranked_comps(stats: &[CompStat]) -> Vec<CompStats> {
+    stats
+        .iter()
+        .filter(|s| s.data_points >= MIN_COMP_GAMES && s.comp_id.is_empty().not())
+        .map(CompStats::from)
+        .sorted_by(|c1, c2| c1.average_position.total_cmp(&c2.average_position))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hero_rates() {
+        let placement = |rank, total_matches| Placement {
+            rank,
+            total_matches,
+        };
+        let tribe = |tribe, impact_average_position| TribeStat {
+            tribe,
+            impact_average_position,
+        };
+        let stat = HeroStat {
+            data_points: 1000,
+            average_position: 4.1,
+            total_offered: 400,
+            total_picked: 100,
+            placement_distribution: vec![placement(1, 150), placement(4, 450), placement(8, 400)],
+            tribe_stats: vec![
+                tribe(20, -0.1),
+                tribe(11, 0.2),
+                tribe(200, -0.5),
+                tribe(14, -0.3),
+            ],
+            ..Default::default()
+        };
+
+        let stats = HeroStats::from(&stat);
+        assert_eq!(stats.pick_rate, Some(0.25));
+        assert_eq!(stats.top4_rate, Some(0.6));
+        assert!(stats.tribes == [MinionType::Murloc, MinionType::Beast]);
+
+        let stats = HeroStats::from(&HeroStat::default());
+        assert_eq!(stats.pick_rate, None);
+        assert_eq!(stats.top4_rate, None);
+    }
+
+    #[test]
+    fn comp_ranking() {
+        let comp = |comp_id: &str, data_points, average_placement| CompStat {
+            comp_id: comp_id.into(),
+            data_points,
+            average_placement,
+            placement_distribution: vec![
+                Placement {
+                    rank: 2,
+                    total_matches: data_points / 2,
+                },
+                Placement {
+                    rank: 6,
+                    total_matches: data_points / 2,
+                },
+            ],
+        };
+        let stats = [
+            comp("beast-goldrinn", 500, 4.2),
+            comp("murloc-brann", 800, 3.9),
+            comp("rare-comp", 20, 1.5),
+            comp("", 900, 3.0),
+        ];
+
+        let ranked = ranked_comps(&stats);
+        assert!(
+            ranked
+                .iter()
+                .map(|c| c.name.as_str())
+                .eq(["Murloc Brann", "Beast Goldrinn"])
+        );
+        assert_eq!(ranked[0].top4_rate, Some(0.5));
+        assert_eq!(ranked[0].games, 800);
+    }
+}
@@ -68,7 +68,7 @@ struct CardData {
 #[serde(from = "CardData")]
 pub struct Card {
     pub id: usize,
-    set: usize,
+    pub(crate) set: usize,
 
     pub name: CompactString,
     pub class: EnumSet<Class>,
@@ -133,6 +133,36 @@ impl Card {
         }
     }
 
+    // A neutral minion with no text, for building decks by hand in tests.
+    #[cfg(test)]
+    pub(crate) fn test(
+        id: usize,
+        name: &str,
+        cost: u8,
+        rarity: Rarity,
+    ) -> Self {
+        Self {
+            id,
+            set: 0,
+            name: name.into(),
+            class: EnumSet::empty(),
+            cost,
+            rune_cost: None,
+            card_type: CardType::Minion {
+                attack: cost,
+                health: cost,
+                minion_types: EnumSet::empty(),
+            },
+            rarity,
+            faction: None,
+            text: CompactString::default(),
+            image: CompactString::default(),
+            crop_image: None,
+            flavor_text: CompactString::default(),
+            cosmetic: false,
+        }
+    }
+
     #[must_use]
     pub fn card_set(
         &self,
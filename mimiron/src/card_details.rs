@@ -1,5 +1,7 @@
 use crate::{
-    AGENT, get_access_token,
+    AGENT,
+    authorization::has_blizzard_client_auth,
+    get_access_token,
     keyword::Keyword,
     localization::{Locale, Localize},
 };
@@ -9,9 +11,10 @@ use either::Either::{self, Left, Right};
 use enumset::{EnumSet, EnumSetType};
 use itertools::Itertools;
 use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Display, Formatter},
+    ops::Not,
     str::FromStr,
     time::{Duration, Instant},
 };
@@ -20,6 +23,8 @@ use std::{
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Metadata {
     pub sets: Vec<Set>,
+    #[serde(default)]
+    pub set_groups: Vec<SetGroup>,
     pub types: Vec<Details<u8>>,
     pub rarities: Vec<Details<u8>>,
     pub classes: Vec<Details<u8>>,
@@ -176,24 +181,122 @@ impl<ID> Details<ID> {
     }
 }
 
-static METADATA: RwLock<Option<(Metadata, Instant)>> = RwLock::new(None);
-const REFRESH_RATE: Duration = Duration::from_secs(86400); // a day
+impl Metadata {
+    // English names for printing offline decks without Blizzard credentials.
+    // No sets, so set lookups stay unknown.
+    fn offline() -> Self {
+        let details = |names: &[(u8, &str)]| {
+            names
+                .iter()
+                .map(|&(id, name)| Details {
+                    id,
+                    name: Right(name.into()),
+                })
+                .collect()
+        };
+
+        Self {
+            types: details(&[
+                (3, "Hero"),
+                (4, "Minion"),
+                (5, "Spell"),
+                (7, "Weapon"),
+                (10, "Hero Power"),
+                (39, "Location"),
+            ]),
+            rarities: details(&[
+                (1, "Common"),
+                (2, "Free"),
+                (3, "Rare"),
+                (4, "Epic"),
+                (5, "Legendary"),
+            ]),
+            classes: details(&[
+                (1, "Death Knight"),
+                (2, "Druid"),
+                (3, "Hunter"),
+                (4, "Mage"),
+                (5, "Paladin"),
+                (6, "Priest"),
+                (7, "Rogue"),
+                (8, "Shaman"),
+                (9, "Warlock"),
+                (10, "Warrior"),
+                (12, "Neutral"),
+                (14, "Demon Hunter"),
+            ]),
+            minion_types: details(&[
+                (1, "Blood Elf"),
+                (2, "Draenei"),
+                (3, "Dwarf"),
+                (4, "Gnome"),
+                (6, "Human"),
+                (7, "Night Elf"),
+                (8, "Orc"),
+                (9, "Tauren"),
+                (10, "Troll"),
+                (11, "Undead"),
+                (14, "Murloc"),
+                (15, "Demon"),
+                (17, "Mech"),
+                (18, "Elemental"),
+                (20, "Beast"),
+                (21, "Totem"),
+                (23, "Pirate"),
+                (24, "Dragon"),
+                (26, "All"),
+                (43, "Quilboar"),
+                (88, "Half-Orc"),
+                (92, "Naga"),
+                (93, "Old God"),
+                (94, "Pandaren"),
+                (95, "Gronn"),
+            ]),
+            spell_schools: details(&[
+                (1, "Arcane"),
+                (2, "Fire"),
+                (3, "Frost"),
+                (4, "Nature"),
+                (5, "Holy"),
+                (6, "Shadow"),
+                (7, "Fel"),
+            ]),
+            ..Self::default()
+        }
+    }
+}
+
+// The time is `None` for the offline fallback, so it is replaced as soon as Blizzard's
+// metadata can be fetched.
+static METADATA: RwLock<Option<(Metadata, Option<Instant>)>> = RwLock::new(None);
+const REFRESH_RATE: Duration = Duration::from_hours(24); // a day
+
+fn internal_get_metadata() -> Option<Metadata> {
+    // Offline decks can be printed without Blizzard credentials.
+    if has_blizzard_client_auth().not() {
+        return None;
+    }
 
-fn internal_get_metadata() -> Metadata {
     AGENT
         .get("https://us.api.blizzard.com/hearthstone/metadata")
         .header("Authorization", format!("Bearer {}", get_access_token()))
         .call()
         .and_then(|mut res| res.body_mut().read_json::<Metadata>())
-        .unwrap_or_default()
+        .inspect_err(|e| tracing::warn!("Could not get Blizzard's metadata: {e}"))
+        .ok()
 }
 
 pub(crate) fn get_metadata() -> MappedRwLockReadGuard<'static, Metadata> {
-    let last_update = METADATA.read().as_ref().map(|o| (o.0.sets.is_empty(), o.1));
-    if last_update.is_none_or(|(empty, t)| empty || t.elapsed() >= REFRESH_RATE) {
-        _ = METADATA
-            .write()
-            .insert((internal_get_metadata(), Instant::now()));
+    let last_update = METADATA.read().as_ref().map(|o| o.1);
+    if last_update.is_none_or(|t| t.is_none_or(|t| t.elapsed() >= REFRESH_RATE)) {
+        match internal_get_metadata() {
+            Some(metadata) => _ = METADATA.write().insert((metadata, Some(Instant::now()))),
+            // Keep stale metadata over the fallback.
+            None if last_update.is_none() => {
+                _ = METADATA.write().insert((Metadata::offline(), None));
+            }
+            None => {}
+        }
     }
 
     RwLockReadGuard::map(METADATA.read(), |c| &c.as_ref().unwrap().0)
@@ -203,9 +306,19 @@ pub(crate) fn get_metadata() -> MappedRwLockReadGuard<'static, Metadata> {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Set {
     id: usize,
+    #[serde(default)]
+    slug: CompactString,
     name: LocalizedName,
     alias_set_ids: Option<Vec<usize>>,
 }
+impl Set {
+    fn has_id(
+        &self,
+        id: usize,
+    ) -> bool {
+        self.id == id || self.alias_set_ids.iter().flatten().contains(&id)
+    }
+}
 impl Localize for Set {
     fn in_locale(
         &self,
@@ -215,6 +328,50 @@ impl Localize for Set {
     }
 }
 
+// Fixed metadata for tests, so they give the same results with or without Blizzard
+// credentials: the offline names, and a couple of sets with English and German names.
+#[cfg(test)]
+pub(crate) fn use_test_metadata() {
+    let set = |id: usize, slug: &str, en: &str, de: &str| {
+        let en = CompactString::from(en);
+        Set {
+            id,
+            slug: slug.into(),
+            name: LocalizedName {
+                deDE: de.into(),
+                enUS: en.clone(),
+                esES: en.clone(),
+                esMX: en.clone(),
+                frFR: en.clone(),
+                itIT: en.clone(),
+                jaJP: en.clone(),
+                koKR: en.clone(),
+                plPL: en.clone(),
+                ptBR: en.clone(),
+                ruRU: en.clone(),
+                thTH: en.clone(),
+                zhCN: None,
+                zhTW: en,
+            },
+            alias_set_ids: None,
+        }
+    };
+
+    let metadata = Metadata {
+        sets: vec![
+            set(CORE_SET_ID, "core", "Core", "Basis"),
+            set(
+                1905,
+                "festival-of-legends",
+                "Festival of Legends",
+                "Festival der Legenden",
+            ),
+        ],
+        ..Metadata::offline()
+    };
+    _ = METADATA.write().insert((metadata, Some(Instant::now())));
+}
+
 pub(crate) fn get_set_by_id(
     id: usize,
     locale: Locale,
@@ -222,7 +379,7 @@ pub(crate) fn get_set_by_id(
     get_metadata()
         .sets
         .iter()
-        .find(|s| s.id == id || s.alias_set_ids.iter().flatten().contains(&id))
+        .find(|s| s.has_id(id))
         .map_or_else(
             || match id {
                 1453 => locale.battlegrounds().into(),
@@ -234,7 +391,41 @@ pub(crate) fn get_set_by_id(
         )
 }
 
-#[derive(EnumSetType, Hash, Deserialize)]
+// Standard, Wild, etc. Lists which sets are legal in which format.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetGroup {
+    slug: CompactString,
+    #[serde(default)]
+    card_sets: Vec<CompactString>,
+}
+
+/// Whether the set is part of the set group (`standard`, `wild`, etc.).
+/// `None` when either is unknown.
+pub(crate) fn is_set_in_group(
+    id: usize,
+    group: &str,
+) -> Option<bool> {
+    let metadata = get_metadata();
+    let set = metadata.sets.iter().find(|s| s.has_id(id))?;
+    let group = metadata.set_groups.iter().find(|g| g.slug == group)?;
+
+    Some(group.card_sets.contains(&set.slug))
+}
+
+// Blizzard's ID for the Core set. Offline cards only know whether they are Core.
+pub(crate) const CORE_SET_ID: usize = 1637;
+
+// Core sets are free for all players and cannot be crafted.
+pub(crate) fn is_core_set(id: usize) -> bool {
+    get_metadata()
+        .sets
+        .iter()
+        .find(|s| s.has_id(id))
+        .map_or(id == CORE_SET_ID, |s| s.slug.starts_with("core"))
+}
+
+#[derive(EnumSetType, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")] // for Firestone's API.
 pub enum Class {
     DeathKnight,
@@ -330,6 +521,24 @@ impl Class {
             Self::Warrior => (200, 21, 24),
         }
     }
+
+    /// The class's original hero, for deck codes built without one.
+    #[must_use]
+    pub const fn hero_id(self) -> usize {
+        match self {
+            Self::DeathKnight => 78_065,
+            Self::DemonHunter => 56_550,
+            Self::Druid => 274,
+            Self::Hunter => 31,
+            Self::Mage => 637,
+            Self::Paladin => 671,
+            Self::Priest => 813,
+            Self::Rogue => 930,
+            Self::Shaman => 1066,
+            Self::Warlock => 893,
+            Self::Warrior => 7,
+        }
+    }
 }
 
 impl Localize for EnumSet<Class> {
@@ -345,8 +554,7 @@ impl Localize for EnumSet<Class> {
                     .classes
                     .iter()
                     .find(|det| det.id == 12) // Neutral
-                    .expect("Neutral (12) always exists")
-                    .name(locale)
+                    .map_or("UNKNOWN".into(), |det| det.name(locale))
             })
     }
 }
@@ -424,6 +632,17 @@ impl Rarity {
             Self::Noncollectible => (0, 204, 255),
         }
     }
+
+    #[must_use]
+    pub const fn craft_cost(&self) -> u32 {
+        match self {
+            Self::Legendary => 1600,
+            Self::Epic => 400,
+            Self::Rare => 100,
+            Self::Common => 40,
+            Self::Free | Self::Noncollectible => 0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -581,17 +800,35 @@ impl Localize for EnumSet<MinionType> {
                     .types
                     .iter()
                     .find(|det| det.id == 4) // 4 for Minion
-                    .expect("Minion (4) always exists")
-                    .name(locale)
+                    .map_or("UNKNOWN".into(), |det| det.name(locale))
             })
     }
 }
 
-#[derive(Clone, Copy, Deserialize)]
+#[derive(Clone, Copy, Default, Deserialize)]
 pub struct RuneCost {
-    blood: u8,
-    frost: u8,
-    unholy: u8,
+    pub(crate) blood: u8,
+    pub(crate) frost: u8,
+    pub(crate) unholy: u8,
+}
+impl RuneCost {
+    /// The runes needed to play both cards.
+    #[must_use]
+    pub fn max(
+        self,
+        other: Self,
+    ) -> Self {
+        Self {
+            blood: self.blood.max(other.blood),
+            frost: self.frost.max(other.frost),
+            unholy: self.unholy.max(other.unholy),
+        }
+    }
+
+    #[must_use]
+    pub const fn count(&self) -> u8 {
+        self.blood + self.frost + self.unholy
+    }
 }
 
 impl Display for RuneCost {
@@ -607,6 +844,18 @@ impl Display for RuneCost {
     }
 }
 
+// 3 for Hero, 4 for Minion, 5 for Spell, 7 for Weapon, 10 for Hero Power, 39 for Location.
+pub(crate) fn get_type_name(
+    id: u8,
+    locale: Locale,
+) -> CompactString {
+    get_metadata()
+        .types
+        .iter()
+        .find(|det| det.id == id)
+        .map_or("UNKNOWN".into(), |det| det.name(locale))
+}
+
 #[derive(Clone, Copy)]
 pub enum CardType {
     Hero {
@@ -647,14 +896,7 @@ impl Localize for CardType {
                 // Not sure what f.alternate() is used for right now.
                 let colon = if f.alternate() { ":" } else { "" };
 
-                let get_type = |i| {
-                    get_metadata()
-                        .types
-                        .iter()
-                        .find(|det| det.id == i)
-                        .unwrap()
-                        .name(self.1)
-                };
+                let get_type = |i| get_type_name(i, self.1);
 
                 match self.0 {
                     CardType::Hero { armor } => {
@@ -1,14 +1,19 @@
 use crate::{
     AGENT,
     card::Card,
-    card_details::{CardType, Class, Details},
+    card_details::{
+        CardType, Class, Details, MinionType, Rarity, RuneCost, SpellSchool, get_type_name,
+        is_core_set, is_set_in_group,
+    },
     get_access_token,
-    hearth_sim::validate_id,
+    hearth_sim::{get_hearth_sim_card, is_hearth_sim_tourist, validate_id},
     localization::{Locale, Localize},
+    meta::CardStats,
 };
 use anyhow::{Result, anyhow};
 use colored::Colorize;
 use compact_str::{CompactString, ToCompactString, format_compact};
+use enumset::EnumSet;
 use itertools::Itertools;
 use serde::Deserialize;
 use std::{
@@ -18,7 +23,29 @@ use std::{
     str::FromStr,
 };
 
-pub use crate::deck_image::ImageOptions;
+pub use crate::{
+    deck_export::Export,
+    deck_image::ImageOptions,
+    deck_import::{ImportIssue, ImportOptions, import},
+};
+
+/// Draw and mulligan odds, calculated or simulated.
+pub mod odds {
+    pub use crate::{
+        deck_odds::{Mulligan, Odds},
+        deck_sim::{
+            HeldCard, MulliganPolicy, SIMULATED_TURNS, SimulationOptions, SimulationReport,
+            simulate,
+        },
+    };
+}
+
+const ETC_BAND_MANAGER_ID: usize = 90_749;
+const ZILLIAX_DELUXE_3000_ID: usize = 102_983;
+const PRINCE_RENATHAL_ID: usize = 79_767;
+
+// Below this, the closest archetype is more likely a different deck that shares staples.
+const AUTO_TITLE_MIN_SCORE: f64 = 0.5;
 
 #[derive(Clone, Default, Deserialize, Debug, PartialEq)]
 #[serde(from = "String")]
@@ -76,6 +103,18 @@ impl TryFrom<u8> for Format {
         })
     }
 }
+/// The format's ID in deck codes. Deck codes have no ID for custom formats (Tavern Brawls,
+/// etc.), so they are encoded as Standard, and read back as Standard.
+impl From<&Format> for u8 {
+    fn from(value: &Format) -> Self {
+        match value {
+            Format::Wild => 1,
+            Format::Classic => 3,
+            Format::Twist => 4,
+            Format::Standard | Format::Custom(_) => 2,
+        }
+    }
+}
 
 #[derive(Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -109,9 +148,30 @@ pub struct Deck {
     pub class: Class,
     pub cards: Vec<Card>,
     pub sideboard_cards: Vec<Sideboard>,
+    hero: usize,
     invalid_card_ids: Vec<usize>,
+    card_stats: HashMap<usize, CardStats>,
 }
 impl Deck {
+    /// Shows each card's stats from `meta::card_stats` in the console output and the images.
+    #[must_use]
+    pub fn with_card_stats(
+        self,
+        card_stats: HashMap<usize, CardStats>,
+    ) -> Self {
+        Self { card_stats, ..self }
+    }
+
+    pub(crate) fn card_stats_of(
+        &self,
+        card: &Card,
+    ) -> Option<&CardStats> {
+        if self.card_stats.is_empty() {
+            return None;
+        }
+        self.card_stats.get(&validate_id(card.id))
+    }
+
     #[must_use]
     pub fn compare_with(
         &self,
@@ -140,7 +200,350 @@ impl Deck {
     ) -> image::RgbaImage {
         crate::deck_image::get(self, opts)
     }
+
+    /// Dust needed to craft the deck, excluding uncraftable Core cards.
+    ///
+    /// E.T.C.'s band is counted. Zilliax's modules are not, as they come with Zilliax.
+    #[must_use]
+    pub fn dust_cost(&self) -> DustCost {
+        self.cards
+            .iter()
+            .chain(
+                self.sideboard_cards
+                    .iter()
+                    .filter(|sb| sb.sideboard_card.id != ZILLIAX_DELUXE_3000_ID)
+                    .flat_map(|sb| &sb.cards_in_sideboard),
+            )
+            .filter(|c| is_core_set(c.set).not())
+            .fold(DustCost::default(), |mut acc, c| {
+                let cost = c.rarity.craft_cost();
+                match c.rarity {
+                    Rarity::Legendary => acc.legendary += cost,
+                    Rarity::Epic => acc.epic += cost,
+                    Rarity::Rare => acc.rare += cost,
+                    Rarity::Common => acc.common += cost,
+                    Rarity::Free | Rarity::Noncollectible => {}
+                }
+                acc
+            })
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> DeckStats {
+        DeckStats::from_cards(&self.cards)
+    }
+
+    /// The deck as the game client copies it to the clipboard, which the client
+    /// (and `lookup`) can import back.
+    #[must_use]
+    pub fn to_client_text(
+        &self,
+        locale: Locale,
+    ) -> String {
+        let mut buffer = String::new();
+
+        let write_cards = |buffer: &mut String, cards: &[Card]| {
+            for (count, card) in cards.iter().sorted().dedup_with_count() {
+                writeln!(buffer, "# {count}x ({}) {}", card.cost, card.name).ok();
+            }
+        };
+
+        writeln!(buffer, "### {}", self.title).ok();
+        writeln!(
+            buffer,
+            "# {}: {}",
+            locale.class_label(),
+            self.class.in_locale(locale)
+        )
+        .ok();
+        writeln!(
+            buffer,
+            "# {}: {}",
+            locale.format_label(),
+            locale.format_name(&self.format)
+        )
+        .ok();
+        writeln!(buffer, "#").ok();
+
+        write_cards(&mut buffer, &self.cards);
+
+        for sideboard in &self.sideboard_cards {
+            writeln!(buffer, "#").ok();
+            writeln!(
+                buffer,
+                "# {}: {}",
+                locale.sideboard_label(),
+                sideboard.sideboard_card.name
+            )
+            .ok();
+            write_cards(&mut buffer, &sideboard.cards_in_sideboard);
+        }
+
+        writeln!(buffer, "#").ok();
+        writeln!(buffer, "{}", self.to_code()).ok();
+        writeln!(buffer, "#").ok();
+        write!(buffer, "# {}", locale.deck_instructions()).ok();
+
+        buffer
+    }
+
+    /// Encodes the deck's current cards and sideboards into a new deck code.
+    ///
+    /// Useful after editing the cards of a looked up `Deck`. A custom format is encoded as
+    /// Standard, as deck codes have no ID for it.
+    #[must_use]
+    pub fn to_code(&self) -> CompactString {
+        self.raw_data().to_code()
+    }
+
+    /// A deck code that is the same for every code of the same list, regardless of card
+    /// order, reprints, or hero portrait.
+    #[must_use]
+    pub fn canonical_code(&self) -> CompactString {
+        self.raw_data().canonical().to_code()
+    }
+
+    /// A hash of `canonical_code`. Stable across runs and versions, so it can be stored.
+    #[must_use]
+    pub fn list_hash(&self) -> u64 {
+        fnv1a(self.canonical_code().as_bytes())
+    }
+
+    /// Whether both decks have the same cards and sideboards, in the same format.
+    ///
+    /// Unlike comparing `deck_code`s, this ignores card order, reprints, and hero portraits.
+    #[must_use]
+    pub fn same_list_as(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.canonical_code() == other.canonical_code()
+    }
+
+    /// Card IDs as in `canonical_code`, sorted.
+    pub(crate) fn canonical_ids(&self) -> Vec<usize> {
+        self.raw_data().canonical().cards
+    }
+
+    // A Standard deck of the class's original hero, for tests.
+    #[cfg(test)]
+    pub(crate) fn test(
+        class: Class,
+        cards: Vec<Card>,
+    ) -> Self {
+        Self {
+            title: CompactString::default(),
+            deck_code: CompactString::default(),
+            format: Format::Standard,
+            class,
+            cards,
+            sideboard_cards: Vec::new(),
+            hero: class.hero_id(),
+            invalid_card_ids: Vec::new(),
+            card_stats: HashMap::new(),
+        }
+    }
+
+    fn raw_data(&self) -> RawCodeData {
+        RawCodeData {
+            format: self.format.clone(),
+            hero: self.hero,
+            cards: self.cards.iter().map(|c| c.id).collect(),
+            sideboard_cards: self
+                .sideboard_cards
+                .iter()
+                .flat_map(|sb| {
+                    sb.cards_in_sideboard
+                        .iter()
+                        .map(|c| (c.id, sb.sideboard_card.id))
+                })
+                .collect(),
+            deck_code: CompactString::default(),
+        }
+    }
+}
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct DustCost {
+    pub legendary: u32,
+    pub epic: u32,
+    pub rare: u32,
+    pub common: u32,
+}
+impl DustCost {
+    #[must_use]
+    pub const fn total(&self) -> u32 {
+        self.legendary + self.epic + self.rare + self.common
+    }
+}
+impl Localize for DustCost {
+    fn in_locale(
+        &self,
+        locale: Locale,
+    ) -> impl Display {
+        let breakdown = [
+            (Rarity::Legendary, self.legendary),
+            (Rarity::Epic, self.epic),
+            (Rarity::Rare, self.rare),
+            (Rarity::Common, self.common),
+        ]
+        .into_iter()
+        .filter(|(_, cost)| *cost > 0)
+        .map(|(rarity, cost)| format!("{} {cost}", rarity.in_locale(locale)))
+        .join(", ");
+
+        if breakdown.is_empty() {
+            format!("{} Dust", self.total())
+        } else {
+            format!("{} Dust ({breakdown})", self.total())
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DeckStats {
+    /// Number of cards at each mana cost. The last slot holds 7 and above.
+    pub mana_curve: [usize; 8],
+    pub average_cost: f64,
+
+    pub minions: usize,
+    pub spells: usize,
+    pub weapons: usize,
+    pub locations: usize,
+    pub heroes: usize,
+
+    /// Sorted by count, most common first.
+    pub spell_schools: Vec<(SpellSchool, usize)>,
+    /// Sorted by count, most common first. Multi-typed minions count towards each type.
+    pub minion_types: Vec<(MinionType, usize)>,
+
+    /// The runes needed to play every card in the deck.
+    pub runes: RuneCost,
+}
+impl DeckStats {
+    fn from_cards(cards: &[Card]) -> Self {
+        fn tally<T: PartialEq>(
+            counts: &mut Vec<(T, usize)>,
+            item: T,
+        ) {
+            match counts.iter_mut().find(|(t, _)| *t == item) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((item, 1)),
+            }
+        }
+
+        let mut stats = cards.iter().fold(Self::default(), |mut stats, card| {
+            stats.mana_curve[(card.cost as usize).min(7)] += 1;
+
+            match card.card_type {
+                CardType::Minion { minion_types, .. } => {
+                    stats.minions += 1;
+                    for minion_type in minion_types {
+                        tally(&mut stats.minion_types, minion_type);
+                    }
+                }
+                CardType::Spell { school } => {
+                    stats.spells += 1;
+                    if let Some(school) = school {
+                        tally(&mut stats.spell_schools, school);
+                    }
+                }
+                CardType::Weapon { .. } => stats.weapons += 1,
+                CardType::Location { .. } => stats.locations += 1,
+                CardType::Hero { .. } => stats.heroes += 1,
+                CardType::HeroPower | CardType::Unknown => {}
+            }
+
+            if let Some(rune_cost) = card.rune_cost {
+                stats.runes = stats.runes.max(rune_cost);
+            }
+
+            stats
+        });
+
+        let (total_cost, count) = cards
+            .iter()
+            .fold((0u32, 0u32), |(t, n), c| (t + u32::from(c.cost), n + 1));
+        if count > 0 {
+            stats.average_cost = f64::from(total_cost) / f64::from(count);
+        }
+
+        // stable sort keeps ties in the order of the deck list.
+        stats
+            .spell_schools
+            .sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        stats
+            .minion_types
+            .sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        stats
+    }
+}
+impl Localize for DeckStats {
+    fn in_locale(
+        &self,
+        locale: Locale,
+    ) -> impl Display {
+        let mut buffer = String::new();
+
+        let tallest = self.mana_curve.iter().max().copied().unwrap_or_default();
+        writeln!(buffer, "{}:", locale.mana_curve_label()).ok();
+        for (cost, count) in self.mana_curve.iter().enumerate() {
+            let label = if cost == 7 {
+                "7+".into()
+            } else {
+                cost.to_compact_string()
+            };
+            let bar = "#".repeat(*count);
+            let padding = tallest - count;
+            writeln!(buffer, "{label:>4} {bar}{:padding$} {count}", "").ok();
+        }
+        writeln!(
+            buffer,
+            "{}: {:.2}",
+            locale.average_cost_label(),
+            self.average_cost
+        )
+        .ok();
+
+        let types = [
+            (4, self.minions),
+            (5, self.spells),
+            (7, self.weapons),
+            (39, self.locations),
+            (3, self.heroes),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(type_id, count)| format!("{}: {count}", get_type_name(type_id, locale)))
+        .join(", ");
+        writeln!(buffer, "{types}").ok();
+
+        if self.spell_schools.is_empty().not() {
+            let schools = self
+                .spell_schools
+                .iter()
+                .map(|(school, count)| format!("{}: {count}", school.in_locale(locale)))
+                .join(", ");
+            writeln!(buffer, "{schools}").ok();
+        }
+
+        if self.minion_types.is_empty().not() {
+            let minion_types = self
+                .minion_types
+                .iter()
+                .map(|(minion_type, count)| format!("{}: {count}", minion_type.in_locale(locale)))
+                .join(", ");
+            writeln!(buffer, "{minion_types}").ok();
+        }
+
+        if self.runes.count() > 0 {
+            writeln!(buffer, "{}: {}", locale.runes_label(), self.runes).ok();
+        }
+
+        buffer.trim_end().to_owned()
+    }
 }
+
 impl From<DeckData> for Deck {
     fn from(value: DeckData) -> Self {
         Self {
@@ -154,7 +557,9 @@ impl From<DeckData> for Deck {
             class: value.class.id.try_into().unwrap_or(Class::Mage),
             cards: value.cards,
             sideboard_cards: value.sideboard_cards,
+            hero: value.hero.id,
             invalid_card_ids: value.invalid_card_ids,
+            card_stats: HashMap::new(),
         }
     }
 }
@@ -177,6 +582,15 @@ impl Localize for Deck {
         for (card, count) in cards {
             let count = format_count(count);
             writeln!(buffer, "{count:>4} {}", card.in_locale(locale)).ok();
+            if let Some(stats) = self.card_stats_of(card) {
+                writeln!(
+                    buffer,
+                    "{:>4} {}",
+                    "",
+                    stats.in_locale(locale).to_string().dimmed()
+                )
+                .ok();
+            }
         }
 
         {
@@ -213,6 +627,17 @@ pub struct DeckDifference {
     pub deck2_code: CompactString,
     pub deck2_uniques: HashMap<Card, usize>,
 }
+impl DeckDifference {
+    /// `deck1` and `deck2` are the decks compared, for their titles and classes.
+    #[must_use]
+    pub fn get_image(
+        &self,
+        deck1: &Deck,
+        deck2: &Deck,
+    ) -> image::RgbaImage {
+        crate::deck_image::difference(self, deck1, deck2)
+    }
+}
 impl Localize for DeckDifference {
     fn in_locale(
         &self,
@@ -240,10 +665,436 @@ impl Localize for DeckDifference {
     }
 }
 
+/// What a group of decks have in common. See [`compare_decks`].
+pub struct DeckComparison {
+    pub deck_count: usize,
+
+    /// Cards in every deck, with the fewest copies any of them runs.
+    pub core_cards: Vec<(Card, usize)>,
+
+    /// Cards missing from at least one deck. Most included first.
+    pub flex_cards: Vec<FlexCard>,
+
+    /// The core cards, filled up with the most included flex copies. Sideboards are the most
+    /// common ones among the decks.
+    pub consensus: Deck,
+}
+pub struct FlexCard {
+    pub card: Card,
+    /// Number of decks running the card.
+    pub decks: usize,
+    /// Average copies among the decks running the card.
+    pub average_copies: f64,
+}
+impl DeckComparison {
+    #[must_use]
+    pub fn get_image(&self) -> image::RgbaImage {
+        crate::deck_image::comparison(self)
+    }
+}
+impl Localize for DeckComparison {
+    fn in_locale(
+        &self,
+        locale: Locale,
+    ) -> impl Display {
+        let mut f = String::new();
+
+        writeln!(
+            f,
+            "{} ({1}/{1}):",
+            locale.core_cards_label(),
+            self.deck_count
+        )
+        .ok();
+        for (card, count) in &self.core_cards {
+            let count = format_count(*count);
+            writeln!(f, "{count:>4} {}", card.in_locale(locale)).ok();
+        }
+
+        writeln!(f, "\n{}:", locale.flex_cards_label()).ok();
+        for flex in &self.flex_cards {
+            writeln!(
+                f,
+                "{:>3}/{} {:.1}x {}",
+                flex.decks,
+                self.deck_count,
+                flex.average_copies,
+                flex.card.in_locale(locale)
+            )
+            .ok();
+        }
+
+        write!(f, "\n{}", self.consensus.in_locale(locale)).ok();
+
+        f
+    }
+}
+
+/// Compares any number of decks at once: which cards they all run, how often the others
+/// show up, and the list most of them agree on.
+///
+/// The consensus deck takes its class, format, and hero from the first deck, and its size
+/// from the most common deck size. Its title is in `locale`.
+#[allow(clippy::cast_precision_loss)]
+pub fn compare_decks(
+    decks: &[Deck],
+    locale: Locale,
+) -> Result<DeckComparison> {
+    use counter::Counter;
+
+    let Some(first) = decks.first() else {
+        anyhow::bail!("No decks to compare.");
+    };
+
+    let counters = decks
+        .iter()
+        .map(|d| d.cards.iter().collect::<Counter<_>>())
+        .collect::<Vec<_>>();
+
+    // copies of each card in each deck.
+    let copies = counters
+        .iter()
+        .flat_map(|c| c.keys().copied())
+        .unique()
+        .map(|card| (card, counters.iter().map(|c| c[&card]).collect_vec()))
+        .collect::<BTreeMap<_, _>>();
+
+    let (core_cards, mut flex_cards): (Vec<_>, Vec<_>) = copies
+        .iter()
+        .map(|(card, counts)| {
+            let decks_with = counts.iter().filter(|&&n| n > 0).count();
+            (card, counts, decks_with)
+        })
+        .partition(|(_, _, decks_with)| *decks_with == decks.len());
+
+    let core_cards = core_cards
+        .into_iter()
+        .map(|(card, counts, _)| ((*card).clone(), counts.iter().min().copied().unwrap_or(0)))
+        .collect();
+
+    flex_cards.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+    let flex_cards = flex_cards
+        .into_iter()
+        .map(|(card, counts, decks_with)| FlexCard {
+            card: (*card).clone(),
+            decks: decks_with,
+            average_copies: counts.iter().sum::<usize>() as f64 / decks_with as f64,
+        })
+        .collect();
+
+    let size = decks
+        .iter()
+        .map(|d| d.cards.len())
+        .counts()
+        .into_iter()
+        .max_by_key(|&(len, n)| (n, len))
+        .map_or(0, |(len, _)| len);
+
+    let cards = consensus_counts(&copies, size)
+        .into_iter()
+        .flat_map(|(card, count)| std::iter::repeat_n((*card).clone(), count))
+        .collect::<Vec<_>>();
+
+    let sideboard_cards = decks
+        .iter()
+        .flat_map(|d| &d.sideboard_cards)
+        .filter(|sb| cards.contains(&sb.sideboard_card))
+        .into_group_map_by(|sb| sb.sideboard_card.id)
+        .into_iter()
+        .sorted_by_key(|(id, _)| *id)
+        .filter_map(|(_, sideboards)| {
+            let ids = |sb: &Sideboard| {
+                sb.cards_in_sideboard
+                    .iter()
+                    .map(|c| c.id)
+                    .sorted()
+                    .collect_vec()
+            };
+            let most_common = sideboards.iter().map(|sb| ids(sb)).counts();
+            sideboards
+                .into_iter()
+                .max_by_key(|sb| most_common[&ids(sb)])
+                .cloned()
+        })
+        .collect();
+
+    let mut consensus = Deck {
+        title: locale.consensus_title(decks.len()),
+        deck_code: CompactString::default(),
+        format: first.format.clone(),
+        class: first.class,
+        cards,
+        sideboard_cards,
+        hero: first.hero,
+        invalid_card_ids: Vec::new(),
+        card_stats: HashMap::new(),
+    };
+    consensus.deck_code = consensus.to_code();
+
+    Ok(DeckComparison {
+        deck_count: decks.len(),
+        core_cards,
+        flex_cards,
+        consensus,
+    })
+}
+
+// The nth copy of a card is ranked by how many decks run at least n copies of it. The top
+// `size` copies make the list. Ties go to earlier copies, then to card order.
+fn consensus_counts<T: Ord>(
+    copies: &BTreeMap<T, Vec<usize>>,
+    size: usize,
+) -> BTreeMap<&T, usize> {
+    copies
+        .iter()
+        .flat_map(|(card, counts)| {
+            let max = counts.iter().max().copied().unwrap_or(0);
+            (1..=max).map(move |nth| (counts.iter().filter(|&&n| n >= nth).count(), nth, card))
+        })
+        .sorted_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(b.2)))
+        .take(size)
+        .fold(BTreeMap::new(), |mut map, (_, _, card)| {
+            *map.entry(card).or_default() += 1;
+            map
+        })
+}
+
+/// A deck construction rule broken by a deck. See [`validate`].
+#[derive(Debug, PartialEq)]
+pub enum Violation {
+    DeckSize {
+        expected: usize,
+        found: usize,
+    },
+    TooManyCopies {
+        card: CompactString,
+        limit: usize,
+        found: usize,
+    },
+    WrongClass {
+        card: CompactString,
+    },
+    TooManyRunes {
+        found: u8,
+    },
+    SideboardSize {
+        card: CompactString,
+        limit: usize,
+        found: usize,
+    },
+    NotInFormat {
+        card: CompactString,
+        format: Format,
+    },
+}
+impl Display for Violation {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::DeckSize { expected, found } => {
+                write!(f, "Deck has {found} cards instead of {expected}.")
+            }
+            Self::TooManyCopies { card, limit, found } => {
+                write!(f, "{card}: {found} copies. Limit is {limit}.")
+            }
+            Self::WrongClass { card } => write!(f, "{card} is not in the deck's class."),
+            Self::TooManyRunes { found } => {
+                write!(f, "Deck requires {found} runes. Limit is 3.")
+            }
+            Self::SideboardSize { card, limit, found } => {
+                write!(
+                    f,
+                    "{card} has {found} cards in its sideboard. Limit is {limit}."
+                )
+            }
+            Self::NotInFormat { card, format } => write!(f, "{card} is not legal in {format}."),
+        }
+    }
+}
+
+/// The result of [`validate`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Validation {
+    pub violations: Vec<Violation>,
+    /// Cards whose legality in the format could not be checked, because the legal sets of the
+    /// format, or the card's set, are not known. Happens without Blizzard's metadata, such as
+    /// with offline decks.
+    pub unchecked: Vec<CompactString>,
+}
+impl Validation {
+    /// No broken rules, and every card was checked.
+    #[must_use]
+    pub fn is_legal(&self) -> bool {
+        self.violations.is_empty() && self.unchecked.is_empty()
+    }
+}
+
+/// Checks the deck against the construction rules of its format.
+///
+/// Custom formats (Tavern Brawls, etc.) have no known rules and always pass. Every set is
+/// legal in Wild.
+#[must_use]
+pub fn validate(deck: &Deck) -> Validation {
+    validate_with(deck, is_set_in_group, is_hearth_sim_tourist)
+}
+
+fn validate_with(
+    deck: &Deck,
+    set_in_group: impl Fn(usize, &str) -> Option<bool>,
+    is_tourist: impl Fn(usize) -> bool,
+) -> Validation {
+    let mut violations = Vec::new();
+
+    if matches!(deck.format, Format::Custom(_)) {
+        return Validation::default();
+    }
+
+    // deck size
+    let expected = if deck.format != Format::Classic
+        && deck.cards.iter().any(|c| c.id == PRINCE_RENATHAL_ID)
+    {
+        40
+    } else {
+        30
+    };
+    if deck.cards.len() != expected {
+        violations.push(Violation::DeckSize {
+            expected,
+            found: deck.cards.len(),
+        });
+    }
+
+    // copies. Reprints are equal to each other.
+    for (card, found) in deck
+        .cards
+        .iter()
+        .sorted()
+        .dedup_with_count()
+        .map(|(n, c)| (c, n))
+    {
+        let limit = if card.rarity == Rarity::Legendary {
+            1
+        } else {
+            2
+        };
+        if found > limit {
+            violations.push(Violation::TooManyCopies {
+                card: card.name.clone(),
+                limit,
+                found,
+            });
+        }
+    }
+
+    // class. Tourists visit from another class, and allow the cards of their class.
+    let off_class = |c: &&Card| c.class.is_empty().not() && c.class.contains(deck.class).not();
+    let allowed = deck
+        .cards
+        .iter()
+        .filter(off_class)
+        .filter(|c| is_tourist(c.id))
+        .fold(EnumSet::only(deck.class), |acc, c| acc | c.class);
+    for card in deck
+        .cards
+        .iter()
+        .filter(off_class)
+        .unique_by(|c| c.id)
+        .filter(|c| c.class.is_disjoint(allowed))
+    {
+        violations.push(Violation::WrongClass {
+            card: card.name.clone(),
+        });
+    }
+
+    // Death Knight runes
+    let runes = deck
+        .cards
+        .iter()
+        .filter_map(|c| c.rune_cost)
+        .fold(RuneCost::default(), RuneCost::max);
+    if runes.count() > 3 {
+        violations.push(Violation::TooManyRunes {
+            found: runes.count(),
+        });
+    }
+
+    // sideboards
+    for sb in &deck.sideboard_cards {
+        let limit = match sb.sideboard_card.id {
+            ETC_BAND_MANAGER_ID => 3,
+            ZILLIAX_DELUXE_3000_ID => 2,
+            _ => continue,
+        };
+        let found = sb
+            .cards_in_sideboard
+            .iter()
+            .filter(|c| c.cosmetic.not())
+            .count();
+        if found > limit {
+            violations.push(Violation::SideboardSize {
+                card: sb.sideboard_card.name.clone(),
+                limit,
+                found,
+            });
+        }
+    }
+
+    // sets
+    let (set_violations, unchecked) = set_violations(deck, set_in_group);
+    violations.extend(set_violations);
+
+    Validation {
+        violations,
+        unchecked,
+    }
+}
+
+// Every set is legal in Wild. Also returns the cards that could not be checked.
+fn set_violations(
+    deck: &Deck,
+    set_in_group: impl Fn(usize, &str) -> Option<bool>,
+) -> (Vec<Violation>, Vec<CompactString>) {
+    let mut violations = Vec::new();
+    let mut unchecked = Vec::new();
+
+    let group = match deck.format {
+        Format::Standard => "standard",
+        Format::Classic => "classic",
+        Format::Twist => "twist",
+        Format::Wild | Format::Custom(_) => return (violations, unchecked),
+    };
+    for card in deck
+        .cards
+        .iter()
+        .chain(
+            deck.sideboard_cards
+                .iter()
+                .flat_map(|sb| &sb.cards_in_sideboard),
+        )
+        .filter(|c| c.cosmetic.not())
+        .unique_by(|c| c.id)
+    {
+        match set_in_group(card.set, group) {
+            Some(true) => {}
+            Some(false) => violations.push(Violation::NotInFormat {
+                card: card.name.clone(),
+                format: deck.format.clone(),
+            }),
+            None => unchecked.push(card.name.clone()),
+        }
+    }
+
+    (violations, unchecked)
+}
+
 pub struct LookupOptions<'s> {
     code: &'s str,
     locale: Locale,
     format: Option<&'s str>,
+    offline: bool,
+    auto_title: bool,
 }
 
 impl<'s> LookupOptions<'s> {
@@ -253,6 +1104,8 @@ impl<'s> LookupOptions<'s> {
             code,
             locale: Locale::enUS,
             format: None,
+            offline: false,
+            auto_title: false,
         }
     }
     #[must_use]
@@ -269,109 +1122,406 @@ impl<'s> LookupOptions<'s> {
     ) -> Self {
         Self { format, ..self }
     }
+    /// Build the deck from hearthstonejson.com data only, without calling Blizzard's servers.
+    /// Needs no Blizzard credentials, but card data is in English only.
+    #[must_use]
+    pub const fn offline(
+        self,
+        offline: bool,
+    ) -> Self {
+        Self { offline, ..self }
+    }
+    /// Name decks without a pasted title after their closest meta archetype. This fetches
+    /// Firestone's meta decks, so it is off by default. Skipped offline, and in formats
+    /// without meta decks.
+    #[must_use]
+    pub const fn with_auto_title(
+        self,
+        auto_title: bool,
+    ) -> Self {
+        Self { auto_title, ..self }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-struct RawCodeData {
-    format: Format,
-    hero: usize,
-    cards: Vec<usize>,
-    sideboard_cards: Vec<(usize, usize)>,
-    deck_code: CompactString,
+/// Why a deck code could not be read. Offsets are in bytes into the decoded code, except for
+/// `Base64`, where it is the position in the code as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckCodeError {
+    Base64 {
+        offset: usize,
+    },
+    /// Deck codes start with a zero byte and then version 1.
+    Version {
+        byte: u8,
+        offset: usize,
+    },
+    UnknownFormat {
+        byte: usize,
+        offset: usize,
+    },
+    /// The code ends in the middle of a number or a list.
+    Truncated {
+        offset: usize,
+    },
+    /// A number that is too large or too long.
+    InvalidVarint {
+        offset: usize,
+    },
+    TrailingBytes {
+        offset: usize,
+    },
+    /// The code says it has sideboards but has none.
+    MissingSideboard {
+        offset: usize,
+    },
 }
-
-impl RawCodeData {
-    fn from_code(code: &str) -> Option<Self> {
-        // Deckstring encoding: https://hearthsim.info/docs/deckstrings/
-
+impl Display for DeckCodeError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Invalid deck code: ")?;
+        match self {
+            Self::Base64 { offset } => write!(f, "unexpected character at position {offset}."),
+            Self::Version { byte, offset } => {
+                write!(f, "unsupported version header {byte} at byte {offset}.")
+            }
+            Self::UnknownFormat { byte, offset } => {
+                write!(f, "unknown format {byte} at byte {offset}.")
+            }
+            Self::Truncated { offset } => {
+                write!(f, "code ends early at byte {offset}. It may be cut off.")
+            }
+            Self::InvalidVarint { offset } => write!(f, "malformed number at byte {offset}."),
+            Self::TrailingBytes { offset } => write!(f, "unexpected data at byte {offset}."),
+            Self::MissingSideboard { offset } => {
+                write!(f, "sideboard missing at byte {offset}. It may be cut off.")
+            }
+        }
+    }
+}
+impl std::error::Error for DeckCodeError {}
+
+// Reads the deck code's bytes, keeping track of the offset for error reporting.
+struct CodeReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+impl CodeReader<'_> {
+    fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, DeckCodeError> {
+        let byte = *self
+            .bytes
+            .get(self.offset)
+            .ok_or(DeckCodeError::Truncated {
+                offset: self.offset,
+            })?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<usize, DeckCodeError> {
+        let start = self.offset;
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as usize)
+                .checked_shl(shift)
+                .filter(|n| n >> shift == (byte & 0x7F) as usize)
+                .ok_or(DeckCodeError::InvalidVarint { offset: start })?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DeckCodeError::InvalidVarint { offset: start })
+    }
+
+    // Each section is a count followed by that many entries.
+    fn section<T>(
+        &mut self,
+        mut entry: impl FnMut(&mut Self) -> Result<T, DeckCodeError>,
+    ) -> Result<Vec<T>, DeckCodeError> {
+        let len = self.varint()?;
+        (0..len).map(|_| entry(self)).collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct RawCodeData {
+    format: Format,
+    hero: usize,
+    cards: Vec<usize>,
+    sideboard_cards: Vec<(usize, usize)>,
+    deck_code: CompactString,
+}
+
+impl RawCodeData {
+    fn from_code(code: &str) -> Result<Self, DeckCodeError> {
+        // Deckstring encoding: https://hearthsim.info/docs/deckstrings/
+
         use base64::{
-            alphabet,
+            DecodeError, alphabet,
             engine::{DecodePaddingMode, Engine as _, GeneralPurpose, GeneralPurposeConfig},
         };
-        use nom::{
-            Parser,
-            branch::alt,
-            bytes::{tag, take, take_while_m_n},
-            combinator::{recognize, success},
-            multi::length_count,
-            number::u8,
-            sequence::preceded,
-        };
 
         const CONFIG: GeneralPurposeConfig =
             GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
         const ENGINE: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, CONFIG);
 
-        #[allow(clippy::cast_possible_truncation)]
-        fn varint<'a>() -> impl Parser<&'a [u8], Output = usize, Error = ()> {
-            let is_ongoing = |b| b & 0x80 != 0;
-
-            recognize(take_while_m_n(0, 8, is_ongoing).and(take(1u8))).map_opt(|p: &[u8]| {
-                p.iter().enumerate().try_fold(0, |acc, (idx, byte)| {
-                    ((*byte as usize) & 0x7F)
-                        .checked_shl(idx as u32 * 7)
-                        .map(|n| acc | n)
-                })
-            })
-        }
+        // URL-safe base64, used by some deck sites.
+        let code = code.replace('-', "+").replace('_', "/");
 
-        let decoded = ENGINE.decode(code).ok()?;
-        let decoded = decoded.as_slice();
+        let decoded = ENGINE.decode(&code).map_err(|e| DeckCodeError::Base64 {
+            offset: match e {
+                DecodeError::InvalidByte(offset, _) | DecodeError::InvalidLastSymbol(offset, _) => {
+                    offset
+                }
+                DecodeError::InvalidLength(_) | DecodeError::InvalidPadding => code.len(),
+            },
+        })?;
 
         #[cfg(debug_assertions)]
         {
-            let raw_code = nom::combinator::iterator(decoded, varint())
-                .fuse()
-                .join(", ");
+            let raw_code = decoded.iter().join(", ");
             tracing::info!(code, raw_code);
         }
 
-        preceded(
-            tag([0, 1].as_slice()),
-            (
-                // format
-                u8().map(|f| f.try_into().unwrap_or_default()),
-                // hero
-                preceded(u8(), varint()),
-                // cards
-                (
-                    // single cards
-                    length_count(u8(), varint()),
-                    // double cards
-                    length_count(u8(), varint().map(|id| [id; 2])),
-                    // n-count cards
-                    length_count(
-                        u8(),
-                        (varint(), u8()).map(|(id, n)| [id].repeat(n as usize)),
-                    ),
-                )
-                    .map(|(v1, v2, vn)| {
-                        v1.into_iter()
-                            .chain(v2.into_iter().flatten())
-                            .chain(vn.into_iter().flatten())
-                            .collect()
-                    }),
-                // sideboard
-                alt((
-                    preceded(
-                        tag([1].as_slice()),
-                        length_count(u8(), (varint(), varint())),
-                    ),
-                    success(Vec::new()),
-                )),
-            )
-                .map(|(format, hero, cards, sideboard_cards)| Self {
-                    format,
-                    hero,
-                    cards,
-                    sideboard_cards,
-                    deck_code: ENGINE.encode(decoded).into(), // Hearthstone requires base64 padding
-                }),
-        )
-        .parse_complete(decoded)
-        .map(|(_, rd)| rd)
-        .ok()
+        let mut reader = CodeReader {
+            bytes: &decoded,
+            offset: 0,
+        };
+
+        // reserved byte and version
+        for expected in [0, 1] {
+            let offset = reader.offset;
+            let byte = reader.byte()?;
+            if byte != expected {
+                return Err(DeckCodeError::Version { byte, offset });
+            }
+        }
+
+        let offset = reader.offset;
+        let format = reader.varint()?;
+        let format = u8::try_from(format)
+            .ok()
+            .and_then(|f| Format::try_from(f).ok())
+            .ok_or(DeckCodeError::UnknownFormat {
+                byte: format,
+                offset,
+            })?;
+
+        // Always one hero.
+        let hero = reader
+            .section(CodeReader::varint)?
+            .first()
+            .copied()
+            .unwrap_or_default();
+
+        let mut cards = reader.section(CodeReader::varint)?;
+        cards.extend(
+            reader
+                .section(CodeReader::varint)?
+                .into_iter()
+                .flat_map(|id| [id; 2]),
+        );
+        for (id, count) in reader.section(|r| Ok((r.varint()?, r.varint()?)))? {
+            cards.extend(std::iter::repeat_n(id, count));
+        }
+
+        // Older codes end here, before sideboards were added to the game.
+        let mut sideboard_cards = Vec::new();
+        if reader.is_empty().not() {
+            let offset = reader.offset;
+            match reader.byte()? {
+                0 => {}
+                1 if reader.is_empty() => {
+                    return Err(DeckCodeError::MissingSideboard {
+                        offset: reader.offset,
+                    });
+                }
+                1 => {
+                    sideboard_cards = reader.section(|r| Ok((r.varint()?, r.varint()?)))?;
+                    for (id, owner) in reader.section(|r| Ok((r.varint()?, r.varint()?)))? {
+                        sideboard_cards.extend([(id, owner); 2]);
+                    }
+                    for (id, count, owner) in
+                        reader.section(|r| Ok((r.varint()?, r.varint()?, r.varint()?)))?
+                    {
+                        sideboard_cards.extend(std::iter::repeat_n((id, owner), count));
+                    }
+                }
+                _ => return Err(DeckCodeError::TrailingBytes { offset }),
+            }
+        }
+
+        if reader.is_empty().not() {
+            return Err(DeckCodeError::TrailingBytes {
+                offset: reader.offset,
+            });
+        }
+
+        Ok(Self {
+            format,
+            hero,
+            cards,
+            sideboard_cards,
+            deck_code: ENGINE.encode(&decoded).into(), // Hearthstone requires base64 padding
+        })
+    }
+
+    fn canonical(&self) -> Self {
+        // Hero portraits are cosmetic. Use the class's original hero.
+        let hero = get_hearth_sim_card(self.hero)
+            .and_then(|c| c.class.iter().next())
+            .map_or(self.hero, Class::hero_id);
+
+        self.canonical_with(validate_id, hero)
+    }
+
+    fn canonical_with(
+        &self,
+        map_id: impl Fn(usize) -> usize,
+        hero: usize,
+    ) -> Self {
+        let cards = self.cards.iter().map(|&id| map_id(id)).sorted().collect();
+        let sideboard_cards = self
+            .sideboard_cards
+            .iter()
+            .map(|&(id, owner)| (map_id(id), map_id(owner)))
+            .sorted()
+            .collect();
+
+        let mut canonical = Self {
+            format: self.format.clone(),
+            hero,
+            cards,
+            sideboard_cards,
+            deck_code: CompactString::default(),
+        };
+        canonical.deck_code = canonical.to_code();
+        canonical
     }
+
+    fn to_code(&self) -> CompactString {
+        // Deckstring encoding: https://hearthsim.info/docs/deckstrings/
+
+        use base64::prelude::{BASE64_STANDARD, Engine as _};
+
+        #[allow(clippy::cast_possible_truncation)]
+        fn write_varint(
+            buf: &mut Vec<u8>,
+            mut n: usize,
+        ) {
+            while n >= 0x80 {
+                buf.push((n as u8 & 0x7F) | 0x80);
+                n >>= 7;
+            }
+            buf.push(n as u8);
+        }
+
+        // Groups (id, count) pairs into the single, double, and n-count sections.
+        fn write_sections<K: Copy>(
+            buf: &mut Vec<u8>,
+            items: &BTreeMap<K, usize>,
+            mut write_key: impl FnMut(&mut Vec<u8>, K),
+        ) {
+            for section in [1, 2, 0] {
+                let keys = items
+                    .iter()
+                    .filter(|&(_, &count)| match section {
+                        0 => count > 2,
+                        n => count == n,
+                    })
+                    .collect::<Vec<_>>();
+
+                write_varint(buf, keys.len());
+                for (&key, &count) in keys {
+                    write_key(buf, key);
+                    if section == 0 {
+                        write_varint(buf, count);
+                    }
+                }
+            }
+        }
+
+        let cards = self.cards.iter().fold(BTreeMap::new(), |mut map, &id| {
+            *map.entry(id).or_default() += 1;
+            map
+        });
+
+        let sideboard_cards =
+            self.sideboard_cards
+                .iter()
+                .fold(BTreeMap::new(), |mut map, &pair| {
+                    *map.entry(pair).or_default() += 1;
+                    map
+                });
+
+        let mut buf = vec![0, 1, u8::from(&self.format), 1];
+        write_varint(&mut buf, self.hero);
+
+        write_sections(&mut buf, &cards, write_varint);
+
+        if sideboard_cards.is_empty() {
+            buf.push(0);
+        } else {
+            buf.push(1);
+            write_sections(&mut buf, &sideboard_cards, |buf, (id, sb_id)| {
+                write_varint(buf, id);
+                write_varint(buf, sb_id);
+            });
+        }
+
+        BASE64_STANDARD.encode(buf).into() // Hearthstone requires base64 padding
+    }
+}
+
+/// Encodes a deck code from its parts.
+///
+/// `cards` are pairs of card IDs and their counts. `sideboard_cards` are pairs of
+/// the card ID in the sideboard and the ID of the card that owns the sideboard
+/// (E.T.C., Zilliax, etc.), repeated for multiple copies.
+#[must_use]
+pub fn encode_code(
+    hero: usize,
+    format: &Format,
+    cards: &[(usize, usize)],
+    sideboard_cards: &[(usize, usize)],
+) -> CompactString {
+    RawCodeData {
+        format: format.clone(),
+        hero,
+        cards: cards
+            .iter()
+            .flat_map(|&(id, count)| std::iter::repeat_n(id, count))
+            .collect(),
+        sideboard_cards: sideboard_cards.to_vec(),
+        deck_code: CompactString::default(),
+    }
+    .to_code()
+}
+
+/// The canonical form of a deck code, or any input `lookup` accepts. See [`Deck::canonical_code`].
+pub fn canonical_code(code: &str) -> Result<CompactString> {
+    Ok(parse_code(code)?.canonical().to_code())
+}
+
+/// Card IDs as in `canonical_code`, sorted.
+pub(crate) fn canonical_ids(code: &str) -> Result<Vec<usize>> {
+    Ok(parse_code(code)?.canonical().cards)
+}
+
+// 64-bit FNV-1a.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(PRIME)
+    })
 }
 
 pub fn lookup(opts: LookupOptions<'_>) -> Result<Deck> {
@@ -385,28 +1535,93 @@ pub fn lookup(opts: LookupOptions<'_>) -> Result<Deck> {
      * # To use this deck, copy it to your clipboard and create a new deck in Hearthstone
      */
 
-    let raw_data = code
-        // if it is a long code pasted from game or tracker
-        .split_ascii_whitespace()
-        .find_map(RawCodeData::from_code)
-        // if it is a url from the official deck builder
-        .or_else(|| {
-            code.split_terminator(&['=', '?']).find_map(|s| {
-                urlencoding::decode(s)
-                    .as_deref()
-                    .ok()
-                    .and_then(RawCodeData::from_code)
-            })
-        })
-        .ok_or_else(|| anyhow!("Unable to parse deck code. Code may be invalid."))?;
+    let raw_data = parse_code(code)?;
+    let title = pasted_title(code);
+
+    Ok(raw_data_to_deck(opts, raw_data, title))
+}
 
-    let title = code
-        .split_once("###")
+fn pasted_title(code: &str) -> Option<CompactString> {
+    code.split_once("###")
         .and_then(|(_, s)| s.split_once("# ")) // space added to allow for titles that have #1 in them.
         .filter(|(s, _)| !s.trim().is_empty())
-        .map(|(s, _)| s.trim().to_compact_string());
+        .map(|(s, _)| s.trim().to_compact_string())
+}
 
-    Ok(raw_data_to_deck(opts, raw_data, title))
+fn parse_code(code: &str) -> Result<RawCodeData> {
+    // If nothing parses, report on the candidate most likely to be the intended code.
+    // Every deck code starts with "AAE".
+    let mut error: Option<((bool, usize), DeckCodeError)> = None;
+    code_candidates(code)
+        .iter()
+        .find_map(|c| {
+            RawCodeData::from_code(c)
+                .inspect_err(|e| {
+                    let key = (c.starts_with("AAE"), c.len());
+                    if error.is_none_or(|(k, _)| key > k) {
+                        error = Some((key, *e));
+                    }
+                })
+                .ok()
+        })
+        .ok_or_else(|| {
+            error.map_or_else(
+                || anyhow!("Unable to parse deck code. Code may be invalid."),
+                |(_, e)| e.into(),
+            )
+        })
+}
+
+// Deck codes rarely have more than a handful of `/` in them.
+const MAX_CODE_SEGMENTS: usize = 16;
+
+/// Everything in the input that might be a deck code, in the order to try them.
+///
+/// Each whitespace separated token is tried as is first: a raw code, or a line of the game
+/// client's long form. Then, for deck site links, every query and fragment value, and every
+/// run of up to `MAX_CODE_SEGMENTS` path segments that starts with `AAE` (deck codes can
+/// contain `/`), longest first. Tokens without a query are also split on `=` the same way,
+/// for a bare `key=code`. All percent-decoded.
+fn code_candidates(input: &str) -> Vec<CompactString> {
+    let decode = |s: &str| {
+        urlencoding::decode(s).map_or_else(|_| CompactString::from(s), CompactString::from)
+    };
+
+    let mut candidates = Vec::new();
+
+    for token in input.split_ascii_whitespace() {
+        candidates.push(decode(token));
+
+        let url = token.split_once("://").map_or(token, |(_, rest)| rest);
+        let (url, fragment) = url.split_once('#').unwrap_or((url, ""));
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+        let mut url_candidates = query
+            .split('&')
+            .chain(fragment.split('&'))
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(_, value)| decode(value))
+            .collect::<Vec<_>>();
+
+        // Runs start where a code would, and are capped so long inputs stay linear.
+        let mut push_runs = |segments: Vec<&str>, sep: &str| {
+            for start in (0..segments.len()).filter(|&i| segments[i].starts_with("AAE")) {
+                for end in start + 1..=segments.len().min(start + MAX_CODE_SEGMENTS) {
+                    url_candidates.push(decode(&segments[start..end].join(sep)));
+                }
+            }
+        };
+        push_runs(path.split('/').collect(), "/");
+        // Pasted `deckcode=AAE…` without a link around it.
+        if query.is_empty() {
+            push_runs(path.split('=').collect(), "=");
+        }
+
+        url_candidates.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        candidates.extend(url_candidates.into_iter().dedup());
+    }
+
+    candidates
 }
 
 fn raw_data_to_deck(
@@ -463,48 +1678,32 @@ fn raw_data_to_deck(
         Ok(deck)
     };
 
-    let get_dummy_deck = || -> Deck {
-        Deck {
-            title: "Hearthstone Deck".into(),
-            deck_code: raw_data.deck_code.clone(),
-            format: Format::Standard,
-            class: Class::Mage,
-            cards: raw_data.cards.iter().map(|&id| Card::dummy(id)).collect(),
-            sideboard_cards: raw_data
-                .sideboard_cards
-                .iter()
-                .chunk_by(|(_, sb_card)| sb_card)
-                .into_iter()
-                .map(|(&sb_card, sb)| Sideboard {
-                    sideboard_card: Card::dummy(sb_card),
-                    cards_in_sideboard: sb.map(|&(c, _)| Card::dummy(c)).collect(),
-                })
-                .collect(),
-            invalid_card_ids: Vec::new(),
-        }
+    let mut deck = if opts.offline {
+        get_offline_deck(&raw_data)
+    } else {
+        get_deck_w_code()
+            .or_else(|e| {
+                tracing::warn!("Encountered error validating code from Blizzard's servers: {e}. Using direct card data instead.");
+                // this validates reprints by using the original card ID
+                get_deck_w_cards()
+            })
+            .unwrap_or_else(|e| {
+                tracing::warn!("Encountered error validating cards from Blizzard's servers: {e}. Using HearthSim data instead.");
+                get_offline_deck(&raw_data)
+            })
     };
 
-    let mut deck = get_deck_w_code()
-        .or_else(|e| {
-            tracing::warn!("Encountered error validating code from Blizzard's servers: {e}. Using direct card data instead.");
-            // this validates reprints by using the original card ID
-            get_deck_w_cards()
-        })
-        .unwrap_or_else(|e| {
-            tracing::warn!("Encountered error validating cards from Blizzard's servers: {e}. Using dummy data instead.");
-            get_dummy_deck()
-        });
-
     deck.format = opts
         .format
         .as_ref()
         .and_then(|s| s.parse().ok())
         .unwrap_or(raw_data.format);
 
+    let auto_title = title.is_none() && opts.auto_title && opts.offline.not();
     deck.title = title.unwrap_or(deck.title);
 
     // if the deck still has invalid card IDs, add dcard manually
-    for id in deck.invalid_card_ids.iter() {
+    for id in &deck.invalid_card_ids {
         // this potentially makes a lot of calls to Blizzard servers.
         // Tried putting all the invalid cards in a deck but that did not work.
         // could cache results if it ever becomes a problem.
@@ -516,15 +1715,67 @@ fn raw_data_to_deck(
 
     specific_card_adjustments(&mut deck);
 
+    if auto_title
+        && crate::meta::has_meta(&deck.format)
+        && let Some((archetype, _)) = crate::meta::classify(&deck)
+            .inspect_err(|e| tracing::warn!("Could not classify deck: {e}"))
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .filter(|(_, score)| *score >= AUTO_TITLE_MIN_SCORE)
+    {
+        deck.title = format_compact!(
+            "{archetype} - {}",
+            deck.format.to_compact_string().to_uppercase()
+        );
+    }
+
     deck
 }
 
+fn get_offline_deck(raw_data: &RawCodeData) -> Deck {
+    let get_card = |id| get_hearth_sim_card(id).unwrap_or_else(|| Card::dummy(id));
+    let hero = get_hearth_sim_card(raw_data.hero);
+
+    Deck {
+        title: hero.as_ref().map_or_else(
+            || "Hearthstone Deck".into(),
+            |hero| {
+                format_compact!(
+                    "{} - {}",
+                    hero.name,
+                    raw_data.format.to_compact_string().to_uppercase()
+                )
+            },
+        ),
+        deck_code: raw_data.deck_code.clone(),
+        format: raw_data.format.clone(),
+        class: hero
+            .and_then(|hero| hero.class.iter().next())
+            .unwrap_or(Class::Mage),
+        cards: raw_data.cards.iter().map(|&id| get_card(id)).collect(),
+        sideboard_cards: raw_data
+            .sideboard_cards
+            .iter()
+            .into_group_map_by(|(_, sb_card)| *sb_card)
+            .into_iter()
+            .sorted_by_key(|(sb_card, _)| *sb_card)
+            .map(|(sb_card, sb)| Sideboard {
+                sideboard_card: get_card(sb_card),
+                cards_in_sideboard: sb.into_iter().map(|&(c, _)| get_card(c)).collect(),
+            })
+            .collect(),
+        hero: raw_data.hero,
+        invalid_card_ids: Vec::new(),
+        card_stats: HashMap::new(),
+    }
+}
+
 fn specific_card_adjustments(deck: &mut Deck) {
     // This function contains specific adjustments to specific cards as needed.
 
     // Treatments for Zilliax Deluxe 3000
     'zilliax_deluxe_3000: {
-        const ZILLIAX_DELUXE_3000_ID: usize = 102_983;
         let Some(sb) = deck
             .sideboard_cards
             .iter_mut()
@@ -539,12 +1790,13 @@ fn specific_card_adjustments(deck: &mut Deck) {
         let (zilliax_cost, zilliax_attack, zilliax_health) =
             sb.cards_in_sideboard
                 .iter()
-                .fold((0, 0, 0), |(acc_c, acc_a, acc_h), c| {
+                .fold((0u8, 0u8, 0u8), |(acc_c, acc_a, acc_h), c| {
                     let (a, h) = c.stats();
+                    // Unknown cards have a dummy cost of 99.
                     (
-                        acc_c + c.cost,
-                        acc_a + a.unwrap_or_default(),
-                        acc_h + h.unwrap_or_default(),
+                        acc_c.saturating_add(c.cost),
+                        acc_a.saturating_add(a.unwrap_or_default()),
+                        acc_h.saturating_add(h.unwrap_or_default()),
                     )
                 });
 
@@ -588,6 +1840,7 @@ mod deck_code_tests {
                     sideboard_cards: $sb_cards,
                     deck_code: $code.into(),
                 };
+                assert_eq!(expected.to_code(), $code);
                 assert_eq!(RawCodeData::from_code($code).unwrap(), expected);
             }
         };
@@ -625,4 +1878,580 @@ mod deck_code_tests {
             (112361, 90749)
         ],
     );
+
+    #[test]
+    fn codes_in_urls() {
+        let code =
+            "AAECAfHhBASYxAXzyAXO8Qb/9wYNh/YE8OgFhY4G/7oGkMsGoOIG4eoGn/EGrPEGvvEGwvEG4/EGqPcGAAA=";
+        let expected = RawCodeData::from_code(code).unwrap();
+
+        let parse = |input: &str| {
+            code_candidates(input)
+                .iter()
+                .find_map(|c| RawCodeData::from_code(c).ok())
+        };
+
+        let encoded = urlencoding::encode(code);
+        let url_safe = code.replace('+', "-").replace('/', "_");
+
+        for input in [
+            format!("https://hearthstone.blizzard.com/deckbuilder?deckcode={encoded}"),
+            format!("https://www.firestoneapp.com/deckbuilder?deckstring={encoded}&utm=x"),
+            format!("https://example.com/decks/{code}/"),
+            format!("https://example.com/deck/{encoded}?tab=stats"),
+            format!("https://example.com/deck#code={url_safe}"),
+            format!("### Title\n# Class: Death Knight\n#\n{code}\n#"),
+            format!("deckcode={code}"),
+            format!("deckcode={encoded}"),
+            format!("Check this out: x={code} !"),
+        ] {
+            assert_eq!(parse(&input).as_ref(), Some(&expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn long_urls_stay_linear() {
+        let input = format!(
+            "https://example.com{}{}",
+            "/a".repeat(5_000),
+            "/AAE".repeat(2_000)
+        );
+
+        let candidates = code_candidates(&input);
+        let total = candidates.iter().map(CompactString::len).sum::<usize>();
+
+        assert!(candidates.len() <= 1 + 2_000 * MAX_CODE_SEGMENTS);
+        assert!(total <= input.len() * (MAX_CODE_SEGMENTS + 1));
+        assert!(parse_code(&input).is_err());
+    }
+
+    #[test]
+    fn custom_format_encodes_as_standard() {
+        let deck = |format| RawCodeData {
+            format,
+            hero: 7,
+            cards: vec![1, 2, 2],
+            sideboard_cards: vec![],
+            deck_code: CompactString::default(),
+        };
+
+        assert_eq!(u8::from(&Format::Custom("brawl".into())), 2);
+        let code = deck(Format::Custom("brawl".into())).to_code();
+        assert_eq!(code, deck(Format::Standard).to_code());
+        assert_eq!(
+            RawCodeData::from_code(&code).unwrap().format,
+            Format::Standard
+        );
+    }
+
+    #[test]
+    fn canonical_ignores_order() {
+        let deck = |cards: Vec<usize>, sideboard_cards: Vec<(usize, usize)>| RawCodeData {
+            format: Format::Wild,
+            hero: 7,
+            cards,
+            sideboard_cards,
+            deck_code: CompactString::default(),
+        };
+
+        let a = deck(vec![3, 1, 2, 2], vec![(9, 90749), (8, 90749)]);
+        let b = deck(vec![2, 1, 2, 3], vec![(8, 90749), (9, 90749)]);
+        // reprint 4 counts as a copy of 1
+        let c = deck(vec![3, 4, 2, 2], vec![(9, 90749), (8, 90749)]);
+        let reprints = |id| if id == 4 { 1 } else { id };
+
+        let a = a.canonical_with(reprints, 7);
+        assert_eq!(a, b.canonical_with(reprints, 7));
+        assert_eq!(a, c.canonical_with(reprints, 7));
+        assert_eq!(
+            fnv1a(a.deck_code.as_bytes()),
+            fnv1a(c.canonical_with(reprints, 7).deck_code.as_bytes())
+        );
+        assert_ne!(a, deck(vec![1, 2, 3], vec![]).canonical_with(reprints, 7));
+    }
+
+    #[test]
+    fn code_errors() {
+        use base64::prelude::{BASE64_STANDARD, Engine as _};
+
+        let err =
+            |bytes: &[u8]| RawCodeData::from_code(&BASE64_STANDARD.encode(bytes)).unwrap_err();
+
+        assert!(
+            RawCodeData::from_code(&BASE64_STANDARD.encode([0, 1, 2, 1, 7, 0, 0, 0, 0])).is_ok()
+        );
+
+        assert_eq!(
+            RawCodeData::from_code("AAE*").unwrap_err(),
+            DeckCodeError::Base64 { offset: 3 }
+        );
+        assert_eq!(err(&[0, 2]), DeckCodeError::Version { byte: 2, offset: 1 });
+        assert_eq!(
+            err(&[0, 1, 9]),
+            DeckCodeError::UnknownFormat { byte: 9, offset: 2 }
+        );
+        assert_eq!(
+            err(&[0, 1, 2, 1, 0x87]),
+            DeckCodeError::Truncated { offset: 5 }
+        );
+        assert_eq!(
+            err(&[0, 1, 2, 1, 7, 0, 0, 0, 5]),
+            DeckCodeError::TrailingBytes { offset: 8 }
+        );
+        assert_eq!(
+            err(&[0, 1, 2, 1, 7, 0, 0, 0, 1]),
+            DeckCodeError::MissingSideboard { offset: 9 }
+        );
+        assert_eq!(
+            err(&[0, 1, 2, 1, 7, 0, 0, 0, 0, 0]),
+            DeckCodeError::TrailingBytes { offset: 9 }
+        );
+    }
+
+    #[test]
+    fn encode_with_counts() {
+        let code = encode_code(
+            7,
+            &Format::Wild,
+            &[(90749, 1), (76302, 2), (102983, 3)],
+            &[(104948, 90749), (102221, 90749)],
+        );
+
+        let expected = RawCodeData {
+            format: Format::Wild,
+            hero: 7,
+            cards: vec![90749, 76302, 76302, 102983, 102983, 102983],
+            sideboard_cards: vec![(102221, 90749), (104948, 90749)],
+            deck_code: code.clone(),
+        };
+        assert_eq!(RawCodeData::from_code(&code).unwrap(), expected);
+    }
+
+    #[test]
+    fn consensus_ranks_copies() {
+        let copies = BTreeMap::from([
+            ("a", vec![2, 1, 2]),
+            ("b", vec![1, 1, 0]),
+            ("c", vec![0, 1, 1]),
+        ]);
+
+        let consensus = consensus_counts(&copies, 3);
+        assert_eq!(consensus, BTreeMap::from([(&"a", 1), (&"b", 1), (&"c", 1)]));
+
+        let consensus = consensus_counts(&copies, 4);
+        assert_eq!(consensus, BTreeMap::from([(&"a", 2), (&"b", 1), (&"c", 1)]));
+    }
+}
+
+#[cfg(test)]
+mod deck_tests {
+    use super::*;
+    use crate::card_details::CORE_SET_ID;
+
+    #[test]
+    fn dust_cost() {
+        let card = |id, rarity| Card::test(id, &format!("Card {id}"), 1, rarity);
+        let core = Card {
+            set: CORE_SET_ID,
+            ..card(3, Rarity::Legendary)
+        };
+        let etc = card(ETC_BAND_MANAGER_ID, Rarity::Legendary);
+        let zilliax = card(ZILLIAX_DELUXE_3000_ID, Rarity::Legendary);
+
+        let mut deck = Deck::test(
+            Class::Mage,
+            vec![
+                etc.clone(),
+                zilliax.clone(),
+                core,
+                card(4, Rarity::Epic),
+                card(4, Rarity::Epic),
+                card(5, Rarity::Common),
+                card(6, Rarity::Free),
+            ],
+        );
+        deck.sideboard_cards = vec![
+            Sideboard {
+                sideboard_card: etc,
+                cards_in_sideboard: vec![card(7, Rarity::Rare), card(8, Rarity::Common)],
+            },
+            Sideboard {
+                sideboard_card: zilliax,
+                cards_in_sideboard: vec![card(9, Rarity::Epic), card(10, Rarity::Rare)],
+            },
+        ];
+
+        let cost = deck.dust_cost();
+        assert_eq!(
+            cost,
+            DustCost {
+                legendary: 3200,
+                epic: 800,
+                rare: 100,
+                common: 80,
+            }
+        );
+        assert_eq!(cost.total(), 4180);
+    }
+
+    #[test]
+    fn stats_from_cards() {
+        let minion = |id, cost, minion_types| Card {
+            card_type: CardType::Minion {
+                attack: 1,
+                health: 1,
+                minion_types,
+            },
+            ..Card::test(id, &format!("Minion {id}"), cost, Rarity::Common)
+        };
+        let spell = |id, cost, school| Card {
+            card_type: CardType::Spell { school },
+            ..Card::test(id, &format!("Spell {id}"), cost, Rarity::Common)
+        };
+        let runes = |card, blood, frost| Card {
+            rune_cost: Some(RuneCost {
+                blood,
+                frost,
+                unholy: 0,
+            }),
+            ..card
+        };
+
+        let cards = [
+            minion(1, 1, EnumSet::only(MinionType::Beast)),
+            runes(minion(2, 2, EnumSet::only(MinionType::Beast)), 2, 0),
+            minion(3, 8, MinionType::Beast | MinionType::Dragon),
+            spell(4, 7, Some(SpellSchool::Frost)),
+            runes(spell(5, 3, Some(SpellSchool::Fire)), 0, 1),
+            spell(6, 10, Some(SpellSchool::Fire)),
+            spell(7, 0, None),
+            Card {
+                card_type: CardType::Weapon {
+                    attack: 2,
+                    durability: 2,
+                },
+                ..Card::test(8, "Weapon", 2, Rarity::Common)
+            },
+        ];
+
+        let stats = DeckStats::from_cards(&cards);
+
+        assert_eq!(stats.mana_curve, [1, 1, 2, 1, 0, 0, 0, 3]);
+        assert!((stats.average_cost - 33.0 / 8.0).abs() < f64::EPSILON);
+        assert_eq!((stats.minions, stats.spells, stats.weapons), (3, 4, 1));
+        assert_eq!((stats.locations, stats.heroes), (0, 0));
+
+        // ties keep deck order
+        assert!(
+            stats.spell_schools == [(SpellSchool::Fire, 2), (SpellSchool::Frost, 1)],
+            "spell schools"
+        );
+        assert!(
+            stats.minion_types == [(MinionType::Beast, 3), (MinionType::Dragon, 1)],
+            "minion types"
+        );
+        assert_eq!(
+            (stats.runes.blood, stats.runes.frost, stats.runes.unholy),
+            (2, 1, 0)
+        );
+
+        assert!(DeckStats::from_cards(&[]).average_cost.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn client_text() {
+        let frostbolt = Card::test(662, "Frostbolt", 2, Rarity::Common);
+        let etc = Card::test(
+            ETC_BAND_MANAGER_ID,
+            "E.T.C., Band Manager",
+            3,
+            Rarity::Legendary,
+        );
+
+        let mut deck = Deck::test(Class::Mage, vec![etc.clone(), frostbolt.clone(), frostbolt]);
+        deck.title = "Test Deck".into();
+        deck.format = Format::Wild;
+        deck.sideboard_cards.push(Sideboard {
+            sideboard_card: etc,
+            cards_in_sideboard: vec![Card::test(1, "Band Member", 1, Rarity::Common)],
+        });
+        // stale code from before an edit
+        deck.deck_code = "AAECAQcAAAAA".into();
+
+        let code = deck.to_code();
+        let text = deck.to_client_text(Locale::enUS);
+        assert_eq!(
+            text,
+            format!(
+                "### Test Deck\n\
+                 # Class: Mage\n\
+                 # Format: Wild\n\
+                 #\n\
+                 # 2x (2) Frostbolt\n\
+                 # 1x (3) E.T.C., Band Manager\n\
+                 #\n\
+                 # Sideboard: E.T.C., Band Manager\n\
+                 # 1x (1) Band Member\n\
+                 #\n\
+                 {code}\n\
+                 #\n\
+                 # To use this deck, copy it to your clipboard and create a new deck in Hearthstone"
+            )
+        );
+
+        assert!(
+            deck.to_client_text(Locale::deDE)
+                .contains("# Format: Wild\n")
+        );
+        assert!(
+            deck.to_client_text(Locale::esMX)
+                .contains("# Formato: Salvaje\n")
+        );
+
+        // what `lookup` reads back
+        assert_eq!(parse_code(&text).unwrap().to_code(), code);
+        assert_eq!(pasted_title(&text).as_deref(), Some("Test Deck"));
+    }
+
+    #[test]
+    fn comparison_labels() {
+        let frostbolt = Card::test(662, "Frostbolt", 2, Rarity::Common);
+        let fireball = Card::test(315, "Fireball", 4, Rarity::Common);
+        let decks = [
+            Deck::test(Class::Mage, vec![frostbolt.clone(), fireball]),
+            Deck::test(Class::Mage, vec![frostbolt]),
+        ];
+
+        let cmp = compare_decks(&decks, Locale::enUS).unwrap();
+        assert_eq!(cmp.consensus.title, "Consensus of 2 Decks");
+        let text = cmp.in_locale(Locale::enUS).to_string();
+        assert!(text.starts_with("Core (2/2):\n"), "{text}");
+        assert!(text.contains("\nFlex:\n"), "{text}");
+
+        let cmp = compare_decks(&decks, Locale::deDE).unwrap();
+        assert_eq!(cmp.consensus.title, "Konsens aus 2 Decks");
+        let text = cmp.in_locale(Locale::deDE).to_string();
+        assert!(text.starts_with("Kern (2/2):\n"), "{text}");
+    }
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::*;
+
+    fn deck(
+        format: Format,
+        class: Class,
+        cards: Vec<Card>,
+    ) -> Deck {
+        Deck {
+            format,
+            ..Deck::test(class, cards)
+        }
+    }
+
+    // 2 copies each of `n` different neutral commons.
+    fn filler(n: usize) -> Vec<Card> {
+        (1..=n)
+            .flat_map(|i| {
+                let card = Card::test(i, &format!("Filler {i}"), 1, Rarity::Common);
+                [card.clone(), card]
+            })
+            .collect()
+    }
+
+    fn class_card(
+        id: usize,
+        name: &str,
+        class: Class,
+    ) -> Card {
+        Card {
+            class: EnumSet::only(class),
+            ..Card::test(id, name, 2, Rarity::Rare)
+        }
+    }
+
+    fn check(deck: &Deck) -> Vec<Violation> {
+        validate_with(deck, |_, _| Some(true), |_| false).violations
+    }
+
+    #[test]
+    fn legal_deck() {
+        let deck = deck(Format::Standard, Class::Mage, filler(15));
+        assert!(validate_with(&deck, |_, _| Some(true), |_| false).is_legal());
+    }
+
+    #[test]
+    fn deck_size() {
+        let mut cards = filler(15);
+        cards.pop();
+        assert_eq!(
+            check(&deck(Format::Standard, Class::Mage, cards)),
+            [Violation::DeckSize {
+                expected: 30,
+                found: 29
+            }]
+        );
+
+        let mut cards = filler(15);
+        cards.pop();
+        cards.push(Card::test(
+            PRINCE_RENATHAL_ID,
+            "Prince Renathal",
+            3,
+            Rarity::Legendary,
+        ));
+        assert_eq!(
+            check(&deck(Format::Wild, Class::Mage, cards.clone())),
+            [Violation::DeckSize {
+                expected: 40,
+                found: 30
+            }]
+        );
+        // Renathal does not exist in Classic.
+        assert_eq!(check(&deck(Format::Classic, Class::Mage, cards)), []);
+    }
+
+    #[test]
+    fn too_many_copies() {
+        let mut cards = filler(14);
+        cards.pop();
+        cards.push(Card::test(100, "Filler 1", 1, Rarity::Common)); // reprint
+        let legendary = Card::test(101, "Legend", 5, Rarity::Legendary);
+        cards.extend([legendary.clone(), legendary]);
+
+        assert_eq!(
+            check(&deck(Format::Standard, Class::Mage, cards)),
+            [
+                Violation::TooManyCopies {
+                    card: "Filler 1".into(),
+                    limit: 2,
+                    found: 3
+                },
+                Violation::TooManyCopies {
+                    card: "Legend".into(),
+                    limit: 1,
+                    found: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_class() {
+        let mut cards = filler(14);
+        cards.extend([
+            class_card(100, "Mage Card", Class::Mage),
+            class_card(101, "Hunter Card", Class::Hunter),
+        ]);
+        let deck = deck(Format::Wild, Class::Mage, cards);
+
+        assert_eq!(
+            check(&deck),
+            [Violation::WrongClass {
+                card: "Hunter Card".into()
+            }]
+        );
+
+        // A Hunter Tourist allows Hunter cards.
+        let mut with_tourist = deck;
+        with_tourist.cards.pop();
+        with_tourist.cards.pop();
+        with_tourist.cards.extend([
+            class_card(101, "Hunter Card", Class::Hunter),
+            class_card(102, "Hunter Tourist", Class::Hunter),
+        ]);
+        assert!(validate_with(&with_tourist, |_, _| Some(true), |id| id == 102).is_legal());
+    }
+
+    #[test]
+    fn too_many_runes() {
+        let rune_card = |id, blood, frost| Card {
+            rune_cost: Some(RuneCost {
+                blood,
+                frost,
+                unholy: 0,
+            }),
+            ..class_card(id, "Rune Card", Class::DeathKnight)
+        };
+
+        let mut cards = filler(14);
+        cards.extend([rune_card(100, 2, 0), rune_card(101, 0, 2)]);
+
+        assert_eq!(
+            check(&deck(Format::Standard, Class::DeathKnight, cards)),
+            [Violation::TooManyRunes { found: 4 }]
+        );
+    }
+
+    #[test]
+    fn sideboard_size() {
+        let mut cards = filler(15);
+        cards.pop();
+        let etc = Card::test(
+            ETC_BAND_MANAGER_ID,
+            "E.T.C., Band Manager",
+            3,
+            Rarity::Legendary,
+        );
+        cards.push(etc.clone());
+
+        let mut deck = deck(Format::Standard, Class::Mage, cards);
+        deck.sideboard_cards.push(Sideboard {
+            sideboard_card: etc,
+            cards_in_sideboard: (200..204)
+                .map(|id| Card::test(id, &format!("Band {id}"), 1, Rarity::Common))
+                .collect(),
+        });
+
+        assert_eq!(
+            check(&deck),
+            [Violation::SideboardSize {
+                card: "E.T.C., Band Manager".into(),
+                limit: 3,
+                found: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn sets() {
+        let mut cards = filler(14);
+        cards.extend(
+            [
+                Card::test(100, "Old", 1, Rarity::Common),
+                Card::test(101, "Unknown", 1, Rarity::Common),
+            ]
+            .into_iter()
+            .map(|c| Card { set: c.id, ..c }),
+        );
+        let in_group = |set, _: &str| match set {
+            100 => Some(false),
+            101 => None,
+            _ => Some(true),
+        };
+
+        for format in [Format::Standard, Format::Classic, Format::Twist] {
+            let deck = deck(format.clone(), Class::Mage, cards.clone());
+            assert_eq!(
+                validate_with(&deck, in_group, |_| false),
+                Validation {
+                    violations: vec![Violation::NotInFormat {
+                        card: "Old".into(),
+                        format
+                    }],
+                    unchecked: vec!["Unknown".into()],
+                }
+            );
+        }
+
+        let deck = deck(Format::Wild, Class::Mage, cards);
+        assert!(validate_with(&deck, in_group, |_| false).is_legal());
+    }
+
+    #[test]
+    fn custom_formats_pass() {
+        let deck = deck(Format::Custom("brawl".into()), Class::Mage, filler(3));
+        assert!(validate_with(&deck, |_, _| None, |_| false).is_legal());
+    }
 }
@@ -0,0 +1,584 @@
+use crate::{
+    CardTextDisplay,
+    card::Card,
+    card_details::{Class, Rarity},
+    deck::{Deck, DeckDifference, Sideboard},
+    localization::{Locale, Localize},
+};
+use compact_str::{CompactString, ToCompactString};
+use itertools::Itertools;
+use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+/// Machine readable output. Unlike `Localize`, the layout of these does not change between
+/// versions: JSON keys, CSV columns, and Markdown table columns stay the same.
+///
+/// Only card names follow the locale the deck was looked up in. Sets are Blizzard's set IDs
+/// in JSON and CSV, and localized names in Markdown.
+pub trait Export {
+    fn to_json(&self) -> String;
+
+    /// Columns are `count,cost,name,rarity,set,section`. `section` is empty for the main deck.
+    fn to_csv(&self) -> String;
+
+    /// Labels and set names are in `locale`.
+    fn to_markdown(
+        &self,
+        locale: Locale,
+    ) -> String;
+}
+
+const CSV_HEADER: &str = "count,cost,name,rarity,set,section";
+
+#[derive(Serialize)]
+struct CardEntry {
+    id: usize,
+    count: usize,
+    cost: u8,
+    name: CompactString,
+    rarity: &'static str,
+    set: usize,
+}
+impl CardEntry {
+    fn new(
+        card: &Card,
+        count: usize,
+    ) -> Self {
+        Self {
+            id: card.id,
+            count,
+            cost: card.cost,
+            name: card.name.clone(),
+            rarity: rarity_key(card.rarity),
+            set: card.set,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SideboardEntry {
+    id: usize,
+    name: CompactString,
+    cards: Vec<CardEntry>,
+}
+impl SideboardEntry {
+    fn new(sideboard: &Sideboard) -> Self {
+        Self {
+            id: sideboard.sideboard_card.id,
+            name: sideboard.sideboard_card.name.clone(),
+            cards: entries(&sideboard.cards_in_sideboard),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DeckEntry {
+    title: CompactString,
+    code: CompactString,
+    format: CompactString,
+    class: Class,
+    cards: Vec<CardEntry>,
+    sideboards: Vec<SideboardEntry>,
+}
+
+#[derive(Serialize)]
+struct DeckListEntry {
+    code: CompactString,
+    cards: Vec<CardEntry>,
+}
+
+#[derive(Serialize)]
+struct DeckDifferenceEntry {
+    shared: Vec<CardEntry>,
+    deck1: DeckListEntry,
+    deck2: DeckListEntry,
+}
+
+impl Export for Deck {
+    fn to_json(&self) -> String {
+        let entry = DeckEntry {
+            title: self.title.clone(),
+            code: self.deck_code.clone(),
+            format: self.format.to_compact_string(),
+            class: self.class,
+            cards: entries(&self.cards),
+            sideboards: self
+                .sideboard_cards
+                .iter()
+                .map(SideboardEntry::new)
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&entry).unwrap_or_default()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut buffer = String::from(CSV_HEADER);
+        write_csv_rows(&mut buffer, &entries(&self.cards), "");
+        for sideboard in &self.sideboard_cards {
+            write_csv_rows(
+                &mut buffer,
+                &entries(&sideboard.cards_in_sideboard),
+                &sideboard.sideboard_card.name,
+            );
+        }
+        buffer
+    }
+
+    fn to_markdown(
+        &self,
+        locale: Locale,
+    ) -> String {
+        let mut buffer = String::new();
+
+        writeln!(buffer, "## {}\n", self.title).ok();
+        writeln!(
+            buffer,
+            "**{}:** {} | **{}:** {}\n",
+            locale.class_label(),
+            self.class.in_locale(locale),
+            locale.format_label(),
+            locale.format_name(&self.format)
+        )
+        .ok();
+        writeln!(buffer, "`{}`\n", self.deck_code).ok();
+
+        write_markdown_table(&mut buffer, &self.cards, locale);
+
+        for sideboard in &self.sideboard_cards {
+            writeln!(
+                buffer,
+                "\n### {}: {}\n",
+                locale.sideboard_label(),
+                sideboard.sideboard_card.name
+            )
+            .ok();
+            write_markdown_table(&mut buffer, &sideboard.cards_in_sideboard, locale);
+        }
+
+        buffer
+    }
+}
+
+impl Export for Sideboard {
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&SideboardEntry::new(self)).unwrap_or_default()
+    }
+
+    fn to_csv(&self) -> String {
+        let mut buffer = String::from(CSV_HEADER);
+        write_csv_rows(
+            &mut buffer,
+            &entries(&self.cards_in_sideboard),
+            &self.sideboard_card.name,
+        );
+        buffer
+    }
+
+    fn to_markdown(
+        &self,
+        locale: Locale,
+    ) -> String {
+        let mut buffer = String::new();
+        writeln!(
+            buffer,
+            "### {}: {}\n",
+            locale.sideboard_label(),
+            self.sideboard_card.name
+        )
+        .ok();
+        write_markdown_table(&mut buffer, &self.cards_in_sideboard, locale);
+        buffer
+    }
+}
+
+impl Export for DeckDifference {
+    fn to_json(&self) -> String {
+        let entry = DeckDifferenceEntry {
+            shared: map_entries(&self.shared_cards),
+            deck1: DeckListEntry {
+                code: self.deck1_code.clone(),
+                cards: map_entries(&self.deck1_uniques),
+            },
+            deck2: DeckListEntry {
+                code: self.deck2_code.clone(),
+                cards: map_entries(&self.deck2_uniques),
+            },
+        };
+
+        serde_json::to_string_pretty(&entry).unwrap_or_default()
+    }
+
+    /// `section` is `shared`, or the code of the deck the card is unique to.
+    fn to_csv(&self) -> String {
+        let mut buffer = String::from(CSV_HEADER);
+        write_csv_rows(&mut buffer, &map_entries(&self.shared_cards), "shared");
+        write_csv_rows(
+            &mut buffer,
+            &map_entries(&self.deck1_uniques),
+            &self.deck1_code,
+        );
+        write_csv_rows(
+            &mut buffer,
+            &map_entries(&self.deck2_uniques),
+            &self.deck2_code,
+        );
+        buffer
+    }
+
+    fn to_markdown(
+        &self,
+        locale: Locale,
+    ) -> String {
+        let to_cards = |map: &HashMap<Card, usize>| {
+            map.iter()
+                .sorted()
+                .flat_map(|(card, count)| std::iter::repeat_n(card.clone(), *count))
+                .collect::<Vec<_>>()
+        };
+
+        let mut buffer = String::from("### Shared\n\n");
+        write_markdown_table(&mut buffer, &to_cards(&self.shared_cards), locale);
+
+        writeln!(buffer, "\n### `{}`\n", self.deck1_code).ok();
+        write_markdown_table(&mut buffer, &to_cards(&self.deck1_uniques), locale);
+
+        writeln!(buffer, "\n### `{}`\n", self.deck2_code).ok();
+        write_markdown_table(&mut buffer, &to_cards(&self.deck2_uniques), locale);
+
+        buffer
+    }
+}
+
+const fn rarity_key(rarity: Rarity) -> &'static str {
+    match rarity {
+        Rarity::Legendary => "legendary",
+        Rarity::Epic => "epic",
+        Rarity::Rare => "rare",
+        Rarity::Common => "common",
+        Rarity::Free => "free",
+        Rarity::Noncollectible => "noncollectible",
+    }
+}
+
+fn entries(cards: &[Card]) -> Vec<CardEntry> {
+    cards
+        .iter()
+        .sorted()
+        .dedup_with_count()
+        .map(|(count, card)| CardEntry::new(card, count))
+        .collect()
+}
+
+fn map_entries(cards: &HashMap<Card, usize>) -> Vec<CardEntry> {
+    cards
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(card, count)| CardEntry::new(card, *count))
+        .collect()
+}
+
+fn write_csv_rows(
+    buffer: &mut String,
+    entries: &[CardEntry],
+    section: &str,
+) {
+    for e in entries {
+        write!(
+            buffer,
+            "\n{},{},{},{},{},{}",
+            e.count,
+            e.cost,
+            csv_field(&e.name),
+            e.rarity,
+            e.set,
+            csv_field(section)
+        )
+        .ok();
+    }
+}
+
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+fn write_markdown_table(
+    buffer: &mut String,
+    cards: &[Card],
+    locale: Locale,
+) {
+    writeln!(buffer, "| Count | Cost | Name | Rarity | Set | Text |").ok();
+    writeln!(buffer, "| ---: | ---: | --- | --- | --- | --- |").ok();
+
+    for (count, card) in cards.iter().sorted().dedup_with_count() {
+        let text = card
+            .text
+            .to_markdown()
+            .replace('\n', " ")
+            .replace('|', "\\|");
+        writeln!(
+            buffer,
+            "| {count} | {} | {} | {} | {} | {text} |",
+            card.cost,
+            card.name.replace('|', "\\|"),
+            rarity_key(card.rarity),
+            card.card_set(locale),
+        )
+        .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck() -> Deck {
+        crate::card_details::use_test_metadata();
+
+        let frostbolt = Card {
+            set: 1637,
+            ..Card::test(662, "Frostbolt", 2, Rarity::Common)
+        };
+        let etc = Card {
+            set: 1905,
+            ..Card::test(90_749, "E.T.C., Band Manager", 3, Rarity::Legendary)
+        };
+
+        let mut deck = Deck::test(Class::Mage, vec![etc.clone(), frostbolt.clone(), frostbolt]);
+        deck.title = "Test Deck".into();
+        deck.deck_code = "AAECAQcAAAAA".into();
+        deck.sideboard_cards.push(Sideboard {
+            sideboard_card: etc,
+            cards_in_sideboard: vec![Card::test(1, "Band Member", 1, Rarity::Rare)],
+        });
+        deck
+    }
+
+    fn difference() -> DeckDifference {
+        let mut other = deck();
+        other.deck_code = "AAECAQcAAAAB".into();
+        other.cards.pop();
+        other
+            .cards
+            .push(Card::test(2, "Arcane Intellect", 3, Rarity::Free));
+        deck().compare_with(&other)
+    }
+
+    #[test]
+    fn deck_export() {
+        let deck = deck();
+        assert_eq!(
+            deck.to_json(),
+            r#"{
+  "title": "Test Deck",
+  "code": "AAECAQcAAAAA",
+  "format": "Standard",
+  "class": "mage",
+  "cards": [
+    {
+      "id": 662,
+      "count": 2,
+      "cost": 2,
+      "name": "Frostbolt",
+      "rarity": "common",
+      "set": 1637
+    },
+    {
+      "id": 90749,
+      "count": 1,
+      "cost": 3,
+      "name": "E.T.C., Band Manager",
+      "rarity": "legendary",
+      "set": 1905
+    }
+  ],
+  "sideboards": [
+    {
+      "id": 90749,
+      "name": "E.T.C., Band Manager",
+      "cards": [
+        {
+          "id": 1,
+          "count": 1,
+          "cost": 1,
+          "name": "Band Member",
+          "rarity": "rare",
+          "set": 0
+        }
+      ]
+    }
+  ]
+}"#
+        );
+        assert_eq!(
+            deck.to_csv(),
+            r#"count,cost,name,rarity,set,section
+2,2,Frostbolt,common,1637,
+1,3,"E.T.C., Band Manager",legendary,1905,
+1,1,Band Member,rare,0,"E.T.C., Band Manager""#
+        );
+        assert_eq!(
+            deck.to_markdown(Locale::enUS),
+            r"## Test Deck
+
+**Class:** Mage | **Format:** Standard
+
+`AAECAQcAAAAA`
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 2 | 2 | Frostbolt | common | Core |  |
+| 1 | 3 | E.T.C., Band Manager | legendary | Festival of Legends |  |
+
+### Sideboard: E.T.C., Band Manager
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 1 | 1 | Band Member | rare | Set 0 |  |
+"
+        );
+        assert!(
+            deck.to_markdown(Locale::deDE)
+                .contains("| Frostbolt | common | Basis |")
+        );
+    }
+
+    #[test]
+    fn sideboard_export() {
+        let sideboard = deck().sideboard_cards.remove(0);
+        assert_eq!(
+            sideboard.to_json(),
+            r#"{
+  "id": 90749,
+  "name": "E.T.C., Band Manager",
+  "cards": [
+    {
+      "id": 1,
+      "count": 1,
+      "cost": 1,
+      "name": "Band Member",
+      "rarity": "rare",
+      "set": 0
+    }
+  ]
+}"#
+        );
+        assert_eq!(
+            sideboard.to_csv(),
+            r#"count,cost,name,rarity,set,section
+1,1,Band Member,rare,0,"E.T.C., Band Manager""#
+        );
+        assert_eq!(
+            sideboard.to_markdown(Locale::enUS),
+            r"### Sideboard: E.T.C., Band Manager
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 1 | 1 | Band Member | rare | Set 0 |  |
+"
+        );
+    }
+
+    #[test]
+    fn difference_export() {
+        let difference = difference();
+        assert_eq!(
+            difference.to_json(),
+            r#"{
+  "shared": [
+    {
+      "id": 662,
+      "count": 1,
+      "cost": 2,
+      "name": "Frostbolt",
+      "rarity": "common",
+      "set": 1637
+    },
+    {
+      "id": 90749,
+      "count": 1,
+      "cost": 3,
+      "name": "E.T.C., Band Manager",
+      "rarity": "legendary",
+      "set": 1905
+    }
+  ],
+  "deck1": {
+    "code": "AAECAQcAAAAA",
+    "cards": [
+      {
+        "id": 662,
+        "count": 1,
+        "cost": 2,
+        "name": "Frostbolt",
+        "rarity": "common",
+        "set": 1637
+      }
+    ]
+  },
+  "deck2": {
+    "code": "AAECAQcAAAAB",
+    "cards": [
+      {
+        "id": 2,
+        "count": 1,
+        "cost": 3,
+        "name": "Arcane Intellect",
+        "rarity": "free",
+        "set": 0
+      }
+    ]
+  }
+}"#
+        );
+        assert_eq!(
+            difference.to_csv(),
+            r#"count,cost,name,rarity,set,section
+1,2,Frostbolt,common,1637,shared
+1,3,"E.T.C., Band Manager",legendary,1905,shared
+1,2,Frostbolt,common,1637,AAECAQcAAAAA
+1,3,Arcane Intellect,free,0,AAECAQcAAAAB"#
+        );
+        assert_eq!(
+            difference.to_markdown(Locale::enUS),
+            r"### Shared
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 1 | 2 | Frostbolt | common | Core |  |
+| 1 | 3 | E.T.C., Band Manager | legendary | Festival of Legends |  |
+
+### `AAECAQcAAAAA`
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 1 | 2 | Frostbolt | common | Core |  |
+
+### `AAECAQcAAAAB`
+
+| Count | Cost | Name | Rarity | Set | Text |
+| ---: | ---: | --- | --- | --- | --- |
+| 1 | 3 | Arcane Intellect | free | Set 0 |  |
+"
+        );
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("Fireball"), "Fireball");
+        assert_eq!(
+            csv_field("E.T.C., Band Manager"),
+            "\"E.T.C., Band Manager\""
+        );
+        assert_eq!(csv_field("The \"Best\" Card"), "\"The \"\"Best\"\" Card\"");
+    }
+}
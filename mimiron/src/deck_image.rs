@@ -1,5 +1,6 @@
 #![allow(
     clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
     clippy::cast_possible_wrap,
     clippy::cast_lossless,
     clippy::cast_sign_loss
@@ -9,9 +10,12 @@ use crate::{
     AGENT,
     card::Card,
     card_details::{CardType, Class, Rarity},
-    deck::Deck,
+    deck::{Deck, DeckComparison, DeckDifference},
     hearth_sim::{get_hearth_sim_crop_image, get_hearth_sim_details},
     localization::Localize,
+    meta::{ArchetypeStats, CardStats, MatchupMatrix, TierList},
+    meta_cards::percent,
+    meta_matchups::heat,
 };
 use ab_glyph::{Font, FontRef, ScaleFont};
 use anyhow::Result;
@@ -41,6 +45,10 @@ const CROP_IMAGE_OFFSET: u32 = SLUG_WIDTH - CROP_WIDTH - INFO_WIDTH;
 const HEADING_SCALE: f32 = 50.0;
 const CARD_NAME_SCALE: f32 = 40.0;
 
+// Stats panel: the mana curve chart takes the first rows, then one row per line of text.
+const CURVE_ROWS: u32 = 5;
+const STATS_PANEL_ROWS: u32 = CURVE_ROWS + 4;
+
 macro_rules! lazy {
     ($s:literal, $f: literal) => {
         (
@@ -80,6 +88,12 @@ pub enum ImageOptions {
     /// Similar to Regular but is either 2 or 3 columns based on "size".
     /// Sideboards are inlined
     Adaptable,
+
+    /// Adds a column with the mana curve, dust cost, card types, and runes.
+    WithStats {
+        /// Groups layout if true, otherwise Adaptable.
+        groups: bool,
+    },
 }
 
 pub fn get(
@@ -87,12 +101,19 @@ pub fn get(
     shape: ImageOptions,
 ) -> RgbaImage {
     match shape {
-        ImageOptions::Groups => img_groups_format(deck),
-        ImageOptions::Adaptable => img_columns_format(deck, None, true),
+        ImageOptions::Groups => img_groups_format(deck, false),
+        ImageOptions::Adaptable => img_columns_format(deck, None, true, false),
         ImageOptions::Regular {
             columns,
             inline_sideboard,
-        } => img_columns_format(deck, NonZeroU32::new(columns as u32), inline_sideboard),
+        } => img_columns_format(
+            deck,
+            NonZeroU32::new(columns as u32),
+            inline_sideboard,
+            false,
+        ),
+        ImageOptions::WithStats { groups: true } => img_groups_format(deck, true),
+        ImageOptions::WithStats { groups: false } => img_columns_format(deck, None, true, true),
     }
 }
 
@@ -100,6 +121,7 @@ fn img_columns_format(
     deck: &Deck,
     col_count: Option<NonZeroU32>,
     inline_sideboard: bool,
+    stats: bool,
 ) -> RgbaImage {
     let ordered_main_deck = deck.cards.iter().sorted().dedup();
     let slug_map = get_cards_slugs(
@@ -120,9 +142,10 @@ fn img_columns_format(
 
         let col_count =
             col_count.map_or_else(|| (length / 15 + (length % 15).min(1)).max(2), u32::from);
-        let cards_in_col = length / col_count + (length % col_count).min(1);
+        let cards_in_col =
+            (length / col_count + (length % col_count).min(1)).max(STATS_PANEL_ROWS * stats as u32);
 
-        let vertical_title = col_count == 1;
+        let vertical_title = col_count == 1 && stats.not();
 
         let mut img = if vertical_title {
             RgbaImage::from_pixel(
@@ -132,17 +155,21 @@ fn img_columns_format(
             )
         } else {
             RgbaImage::from_pixel(
-                COLUMN_WIDTH * col_count + MARGIN,
+                COLUMN_WIDTH * (col_count + stats as u32) + MARGIN,
                 ROW_HEIGHT * (cards_in_col + 1) + 4 * MARGIN,
                 Rgba([255; 4]),
             )
         };
 
-        draw_deck_title(&mut img, deck, vertical_title);
+        draw_deck_title(&mut img, deck, vertical_title, stats);
         if vertical_title {
             img = imageops::rotate90(&img);
         }
 
+        if stats {
+            draw_stats_panel(&mut img, deck, col_count * COLUMN_WIDTH + MARGIN);
+        }
+
         draw_footer(&mut img, deck.class.color());
 
         (img, move |c| {
@@ -216,7 +243,347 @@ fn img_columns_format(
     img
 }
 
-fn img_groups_format(deck: &Deck) -> RgbaImage {
+/// The consensus list over two columns, then the flex cards with how many decks run them.
+pub(crate) fn comparison(cmp: &DeckComparison) -> RgbaImage {
+    let deck = &cmp.consensus;
+    let slug_map = get_cards_slugs(deck, SideboardStyle::Indented);
+
+    let consensus_slugs = deck
+        .cards
+        .iter()
+        .sorted()
+        .dedup()
+        .flat_map(|card| {
+            std::iter::once(&slug_map[&(card.id, Zone::MainDeck)]).chain(
+                deck.sideboard_cards
+                    .iter()
+                    .filter(|sb| sb.sideboard_card.id == card.id)
+                    .flat_map(|sb| sb.cards_in_sideboard.iter().sorted().dedup())
+                    .map(|c| {
+                        &slug_map[&(
+                            c.id,
+                            Zone::Sideboard {
+                                sb_card_id: card.id,
+                            },
+                        )]
+                    }),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let flex_slugs = cmp
+        .flex_cards
+        .par_iter()
+        .map(|flex| {
+            let label = format_compact!("{}/{}", flex.decks, cmp.deck_count);
+            draw_card_slug(
+                &flex.card,
+                SlugLabel::Text(label),
+                Zone::MainDeck,
+                SideboardStyle::Indented,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let consensus_rows = (consensus_slugs.len() as u32).div_ceil(2).max(1);
+    let flex_rows = flex_slugs.len() as u32 + flex_slugs.is_empty().not() as u32;
+
+    let mut img = RgbaImage::from_pixel(
+        COLUMN_WIDTH * (2 + flex_slugs.is_empty().not() as u32) + MARGIN,
+        ROW_HEIGHT * (consensus_rows.max(flex_rows) + 1) + 4 * MARGIN,
+        Rgba([255; 4]),
+    );
+
+    draw_deck_title(&mut img, deck, false, false);
+    draw_footer(&mut img, deck.class.color());
+
+    for (i, slug) in consensus_slugs.into_iter().enumerate() {
+        let (col, row) = (i as u32 / consensus_rows, i as u32 % consensus_rows + 1);
+        _ = img.copy_from(slug, col * COLUMN_WIDTH + MARGIN, row * ROW_HEIGHT + MARGIN);
+    }
+
+    if flex_slugs.is_empty().not() {
+        let flex_col = 2 * COLUMN_WIDTH + MARGIN;
+        _ = img.copy_from(&draw_heading_slug("Flex"), flex_col, ROW_HEIGHT + MARGIN);
+
+        for (i, slug) in flex_slugs.iter().enumerate() {
+            let row = i as u32 + 2;
+            _ = img.copy_from(slug, flex_col, row * ROW_HEIGHT + MARGIN);
+        }
+    }
+
+    img
+}
+
+/// Shared cards in the middle, with the cards unique to each deck on either side.
+pub(crate) fn difference(
+    diff: &DeckDifference,
+    deck1: &Deck,
+    deck2: &Deck,
+) -> RgbaImage {
+    let slugs = |cards: &HashMap<Card, usize>, marker: Option<char>| {
+        cards
+            .iter()
+            .sorted()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(card, &count)| {
+                let label = match marker {
+                    Some(marker) => SlugLabel::Text(format_compact!("{marker}{count}")),
+                    None => SlugLabel::Count(count),
+                };
+                draw_card_slug(card, label, Zone::MainDeck, SideboardStyle::EndOfDeck)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let columns = [
+        (
+            format_compact!("+ {}", deck1.title),
+            Some(deck1.class),
+            slugs(&diff.deck1_uniques, Some('+')),
+        ),
+        (
+            CompactString::from("Shared"),
+            None,
+            slugs(&diff.shared_cards, None),
+        ),
+        (
+            format_compact!("- {}", deck2.title),
+            Some(deck2.class),
+            slugs(&diff.deck2_uniques, Some('-')),
+        ),
+    ];
+
+    let rows = columns
+        .iter()
+        .map(|(_, _, slugs)| slugs.len())
+        .max()
+        .unwrap_or(0) as u32;
+
+    let mut img = RgbaImage::from_pixel(
+        COLUMN_WIDTH * 3 + MARGIN,
+        ROW_HEIGHT * (rows + 2) + 4 * MARGIN,
+        Rgba([255; 4]),
+    );
+
+    draw_text(
+        &mut img,
+        [10, 10, 10, 255],
+        MARGIN,
+        MARGIN,
+        HEADING_SCALE,
+        &format_compact!("{} Deck Comparison", deck1.class.in_en_us()),
+    );
+    draw_footer(&mut img, deck1.class.color());
+
+    for (col, (heading, class, slugs)) in columns.into_iter().enumerate() {
+        let x = col as u32 * COLUMN_WIDTH + MARGIN;
+
+        let heading = match class {
+            Some(class) => draw_class_heading_slug(&heading, class),
+            None => draw_heading_slug(&heading),
+        };
+        _ = img.copy_from(&heading, x, ROW_HEIGHT + MARGIN);
+
+        for (i, slug) in slugs.iter().enumerate() {
+            let row = i as u32 + 2;
+            _ = img.copy_from(slug, x, row * ROW_HEIGHT + MARGIN);
+        }
+    }
+
+    img
+}
+
+/// Winrate heatmap of each row's archetype against each column's.
+pub(crate) fn matchups(matrix: &MatchupMatrix) -> RgbaImage {
+    let size = matrix.archetypes.len() as u32;
+    let cell = |text: &str, color: (u8, u8, u8)| {
+        let mut img = RgbaImage::from_pixel(
+            CROP_HEIGHT,
+            CROP_HEIGHT,
+            Rgba([color.0, color.1, color.2, 255]),
+        );
+        let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, text);
+        let x_offset = CROP_HEIGHT.saturating_sub(tw) / 2;
+        draw_text(
+            &mut img,
+            [10, 10, 10, 255],
+            x_offset,
+            0,
+            CARD_NAME_SCALE,
+            text,
+        );
+        img
+    };
+
+    let mut img = RgbaImage::from_pixel(
+        COLUMN_WIDTH + size * ROW_HEIGHT + MARGIN,
+        ROW_HEIGHT * (size + 1) + 4 * MARGIN,
+        Rgba([255; 4]),
+    );
+
+    _ = img.copy_from(&draw_heading_slug("Matchups"), MARGIN, MARGIN);
+    draw_footer(
+        &mut img,
+        matrix
+            .archetypes
+            .first()
+            .map_or((10, 10, 10), |(_, class)| class.color()),
+    );
+
+    for col in 0..size {
+        let label = (col + 1).to_compact_string();
+        _ = img.copy_from(
+            &cell(&label, (255, 255, 255)),
+            COLUMN_WIDTH + col * ROW_HEIGHT,
+            MARGIN,
+        );
+    }
+
+    for (row, (name, class)) in matrix.archetypes.iter().enumerate() {
+        let y = (row as u32 + 1) * ROW_HEIGHT + MARGIN;
+        let heading = format_compact!("{}. {name}", row + 1);
+        _ = img.copy_from(&draw_class_heading_slug(&heading, *class), MARGIN, y);
+
+        for col in 0..matrix.archetypes.len() {
+            let slug = match matrix.get(row, col) {
+                Some(m) => cell(
+                    &format_compact!("{:.0}", m.winrate * 100.0),
+                    heat(m.winrate),
+                ),
+                None => cell("-", (230, 230, 230)),
+            };
+            _ = img.copy_from(&slug, COLUMN_WIDTH + col as u32 * ROW_HEIGHT, y);
+        }
+    }
+
+    img
+}
+
+/// Each tier under its heading, with a row per archetype.
+pub(crate) fn tier_list(list: &TierList) -> RgbaImage {
+    let row_width = SLUG_WIDTH + CROP_WIDTH;
+
+    // Fetched and resized once per class, rather than once per row.
+    let icons = list
+        .tiers
+        .iter()
+        .flatten()
+        .map(|a| a.class)
+        .unique()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .filter_map(|class| {
+            let icon = get_class_icon(class).ok()?;
+            let icon = imageops::resize(
+                &icon,
+                INFO_WIDTH,
+                CROP_HEIGHT,
+                imageops::FilterType::Gaussian,
+            );
+            Some((class, icon))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let tiers = list
+        .tiers
+        .iter()
+        .enumerate()
+        .filter(|(_, tier)| tier.is_empty().not())
+        .map(|(i, tier)| {
+            let rows = tier
+                .par_iter()
+                .map(|a| draw_archetype_slug(a, icons.get(&a.class), row_width))
+                .collect::<Vec<_>>();
+            (draw_heading_slug(&format_compact!("Tier {}", i + 1)), rows)
+        })
+        .collect::<Vec<_>>();
+
+    let row_count = tiers.iter().map(|(_, rows)| rows.len() + 1).sum::<usize>() as u32;
+
+    let mut img = RgbaImage::from_pixel(
+        row_width + 2 * MARGIN,
+        ROW_HEIGHT * (row_count + 1) + 4 * MARGIN,
+        Rgba([255; 4]),
+    );
+
+    let title = format_compact!(
+        "{} Tier List",
+        list.format.to_compact_string().to_uppercase()
+    );
+    draw_text(
+        &mut img,
+        [10, 10, 10, 255],
+        MARGIN,
+        MARGIN,
+        HEADING_SCALE,
+        &title,
+    );
+    draw_footer(
+        &mut img,
+        list.tiers
+            .iter()
+            .flatten()
+            .next()
+            .map_or((10, 10, 10), |a| a.class.color()),
+    );
+
+    let slugs = tiers
+        .iter()
+        .flat_map(|(heading, rows)| std::iter::once(heading).chain(rows));
+    for (row, slug) in slugs.enumerate() {
+        _ = img.copy_from(slug, MARGIN, (row as u32 + 1) * ROW_HEIGHT + MARGIN);
+    }
+
+    img
+}
+
+// Class icon (already resized), name, then winrate and games on the right, over the class
+// color.
+fn draw_archetype_slug(
+    archetype: &ArchetypeStats,
+    icon: Option<&RgbaImage>,
+    width: u32,
+) -> RgbaImage {
+    let (r, g, b) = archetype.class.color();
+    let mut img = RgbaImage::from_pixel(width, CROP_HEIGHT, Rgba([r, g, b, 255]));
+
+    let offset = icon.map_or(MARGIN, |icon| {
+        _ = img.copy_from(icon, 0, 0);
+        INFO_WIDTH + 10
+    });
+    draw_text(
+        &mut img,
+        [255; 4],
+        offset,
+        0,
+        CARD_NAME_SCALE,
+        &archetype.name,
+    );
+
+    let stats = format_compact!(
+        "{:.1}%  {} games",
+        archetype.winrate * 100.0,
+        archetype.total_games
+    );
+    let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, &stats);
+    draw_text(
+        &mut img,
+        [255; 4],
+        width.saturating_sub(tw + 2 * MARGIN),
+        0,
+        CARD_NAME_SCALE,
+        &stats,
+    );
+
+    img
+}
+
+fn img_groups_format(
+    deck: &Deck,
+    stats: bool,
+) -> RgbaImage {
     let ordered_main_deck = deck.cards.iter().sorted().dedup();
     let slug_map = get_cards_slugs(deck, SideboardStyle::EndOfDeck);
 
@@ -242,12 +609,18 @@ fn img_groups_format(deck: &Deck) -> RgbaImage {
         if deck.sideboard_cards.is_empty().not() {
             columns += 1;
         }
+        if stats {
+            columns += 1;
+        }
 
-        let rows = 1 + class_cards.len().max(neutral_cards.len()).max(
+        let mut rows = 1 + class_cards.len().max(neutral_cards.len()).max(
             deck.sideboard_cards.iter().fold(0, |acc, sb| {
                 acc + (sb.cards_in_sideboard.iter().unique().count() + 1)
             }),
         ) as u32;
+        if stats {
+            rows = rows.max(1 + STATS_PANEL_ROWS);
+        }
 
         RgbaImage::from_pixel(
             columns * COLUMN_WIDTH + MARGIN,
@@ -256,7 +629,14 @@ fn img_groups_format(deck: &Deck) -> RgbaImage {
         )
     };
 
-    draw_deck_title(&mut img, deck, false);
+    draw_deck_title(&mut img, deck, false, stats);
+
+    if stats {
+        // always last column
+        let x_offset = img.width() - COLUMN_WIDTH;
+        draw_stats_panel(&mut img, deck, x_offset);
+    }
+
     draw_footer(&mut img, deck.class.color());
 
     for (i, slug) in class_cards {
@@ -271,8 +651,8 @@ fn img_groups_format(deck: &Deck) -> RgbaImage {
 
     {
         // sideboards
-        // always last column
-        let sb_col = img.width() - COLUMN_WIDTH;
+        // always last column, unless the stats panel is there.
+        let sb_col = img.width() - COLUMN_WIDTH * (1 + stats as u32);
         let mut sb_cursor = 1;
 
         for sb in &deck.sideboard_cards {
@@ -312,13 +692,21 @@ enum SideboardStyle {
     Indented,
 }
 
+// What goes in the rarity square of a card slug.
+enum SlugLabel {
+    Count(usize),
+    Text(CompactString),
+}
+
 fn draw_card_slug(
     card: &Card,
-    count: usize,
+    label: SlugLabel,
     zone: Zone,
     sb_style: SideboardStyle,
 ) -> RgbaImage {
-    assert!(count > 0);
+    if let SlugLabel::Count(count) = label {
+        assert!(count > 0);
+    }
 
     // if card type is Unknown data other than card id is usually junk.
     let (name, cost, rarity) = matches!(card.card_type, CardType::Unknown)
@@ -417,19 +805,23 @@ fn draw_card_slug(
     );
 
     // card count
-    let count = match (count, rarity) {
-        (1, Rarity::Noncollectible) => CompactString::from("!"),
-        (1, Rarity::Legendary) => CompactString::default(),
-        _ => count.to_compact_string(),
+    let label = match (label, rarity) {
+        (SlugLabel::Count(1), Rarity::Noncollectible) => CompactString::from("!"),
+        (SlugLabel::Count(1), Rarity::Legendary) => CompactString::default(),
+        (SlugLabel::Count(count), _) => count.to_compact_string(),
+        (SlugLabel::Text(text), _) => text,
     };
-    let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, &count);
+    // longer labels are shrunk to fit the square.
+    let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, &label);
+    let scale = CARD_NAME_SCALE * (INFO_WIDTH - MARGIN).min(tw) as f32 / tw.max(1) as f32;
+    let (tw, _) = drawing::text_size(scale, &*FONTS[0].0, &label);
     draw_text(
         &mut img,
         [255; 4],
         SLUG_WIDTH - INFO_WIDTH.midpoint(tw),
         0,
-        CARD_NAME_SCALE,
-        &count,
+        scale,
+        &label,
     );
 
     img
@@ -461,22 +853,207 @@ fn get_cards_slugs(
         }))
         .par_bridge()
         .map(|(card, count, zone)| {
-            let slug = draw_card_slug(card, count, zone, sb_style);
+            let mut slug = draw_card_slug(card, SlugLabel::Count(count), zone, sb_style);
+            if let (Zone::MainDeck, Some(stats)) = (zone, deck.card_stats_of(card)) {
+                draw_card_stats(&mut slug, stats);
+            }
             ((card.id, zone), slug)
         })
         .collect()
 }
 
+// Mulligan and drawn winrates, next to the rarity square.
+fn draw_card_stats(
+    slug: &mut RgbaImage,
+    stats: &CardStats,
+) {
+    const SCALE: f32 = 24.0;
+
+    let lines = [
+        format_compact!("Mull {}", percent(stats.mulligan_winrate)),
+        format_compact!("Drawn {}", percent(stats.drawn_winrate)),
+    ];
+    let width = lines
+        .iter()
+        .map(|l| drawing::text_size(SCALE, &*FONTS[0].0, l).0)
+        .max()
+        .unwrap_or_default()
+        + 2 * MARGIN;
+    let x_offset = SLUG_WIDTH - INFO_WIDTH - width;
+
+    drawing::draw_filled_rect_mut(
+        slug,
+        Rect::at(x_offset as i32, 0).of_size(width, CROP_HEIGHT),
+        Rgba([10, 10, 10, 255]),
+    );
+    for (i, line) in lines.iter().enumerate() {
+        drawing::draw_text_mut(
+            slug,
+            Rgba([255; 4]),
+            (x_offset + MARGIN) as i32,
+            (MARGIN + i as u32 * CROP_HEIGHT / 2) as i32,
+            SCALE,
+            &*FONTS[0].0,
+            line,
+        );
+    }
+}
+
 fn draw_heading_slug(heading: &str) -> RgbaImage {
     let mut img = RgbaImage::from_pixel(SLUG_WIDTH, CROP_HEIGHT, Rgba([255; 4]));
     draw_text(&mut img, [10, 10, 10, 255], 15, 0, HEADING_SCALE, heading);
     img
 }
 
+fn draw_class_heading_slug(
+    heading: &str,
+    class: Class,
+) -> RgbaImage {
+    let (r, g, b) = class.color();
+    let mut img = RgbaImage::from_pixel(SLUG_WIDTH, CROP_HEIGHT, Rgba([r, g, b, 255]));
+    draw_text(&mut img, [255; 4], 15, 0, HEADING_SCALE, heading);
+    img
+}
+
+fn draw_stats_panel(
+    canvas: &mut RgbaImage,
+    deck: &Deck,
+    x_offset: u32,
+) {
+    let stats = deck.stats();
+    let text_color = [10, 10, 10, 255];
+
+    let mut img = RgbaImage::from_pixel(
+        SLUG_WIDTH,
+        STATS_PANEL_ROWS * ROW_HEIGHT - MARGIN,
+        Rgba([255; 4]),
+    );
+
+    draw_mana_curve(&mut img, &stats.mana_curve, deck.class.color());
+
+    let types = [
+        ("Minions", stats.minions),
+        ("Spells", stats.spells),
+        ("Weapons", stats.weapons),
+        ("Locations", stats.locations),
+        ("Heroes", stats.heroes),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(name, count)| format!("{count} {name}"))
+    .collect::<Vec<_>>();
+
+    let lines = std::iter::once(format!(
+        "Avg. Cost {:.2}, {} Dust",
+        stats.average_cost,
+        deck.dust_cost().total()
+    ))
+    .chain(types.chunks(3).map(|line| line.join(", ")))
+    .collect::<Vec<_>>();
+
+    for (i, line) in lines.iter().enumerate() {
+        let y = (CURVE_ROWS + i as u32) * ROW_HEIGHT;
+        draw_text(&mut img, text_color, 15, y, CARD_NAME_SCALE, line);
+    }
+
+    if stats.runes.count() > 0 {
+        let label = "Runes";
+        let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, label);
+        let y = (CURVE_ROWS + lines.len() as u32) * ROW_HEIGHT;
+        draw_text(&mut img, text_color, 15, y, CARD_NAME_SCALE, label);
+
+        let rune_size = CROP_HEIGHT / 2;
+        let runes = std::iter::repeat_n([196, 30, 58, 255], stats.runes.blood as usize)
+            .chain(std::iter::repeat_n(
+                [0, 112, 221, 255],
+                stats.runes.frost as usize,
+            ))
+            .chain(std::iter::repeat_n(
+                [30, 160, 60, 255],
+                stats.runes.unholy as usize,
+            ));
+
+        for (i, color) in runes.enumerate() {
+            drawing::draw_filled_circle_mut(
+                &mut img,
+                (
+                    (15 + tw + 10 + rune_size / 2 + i as u32 * (rune_size + MARGIN)) as i32,
+                    (y + CROP_HEIGHT / 2) as i32,
+                ),
+                (rune_size / 2) as i32,
+                Rgba(color),
+            );
+        }
+    }
+
+    _ = canvas.copy_from(&img, x_offset, ROW_HEIGHT + MARGIN);
+}
+
+// Count labels go above the bars and cost labels below them.
+fn draw_mana_curve(
+    img: &mut RgbaImage,
+    mana_curve: &[usize; 8],
+    (r, g, b): (u8, u8, u8),
+) {
+    let text_color = [10, 10, 10, 255];
+
+    let bar_width = (SLUG_WIDTH - 9 * MARGIN) / 8;
+    let baseline = CURVE_ROWS * ROW_HEIGHT - MARGIN - CROP_HEIGHT;
+    let max_bar_height = baseline - CROP_HEIGHT;
+    let tallest = mana_curve.iter().max().copied().unwrap_or_default().max(1) as u32;
+
+    drawing::draw_filled_rect_mut(
+        img,
+        Rect::at(0, baseline as i32).of_size(SLUG_WIDTH, 2),
+        Rgba([169, 169, 169, 255]),
+    );
+
+    for (cost, &count) in mana_curve.iter().enumerate() {
+        let x = MARGIN + cost as u32 * (bar_width + MARGIN);
+        let bar_height = count as u32 * max_bar_height / tallest;
+
+        if bar_height > 0 {
+            drawing::draw_filled_rect_mut(
+                img,
+                Rect::at(x as i32, (baseline - bar_height) as i32).of_size(bar_width, bar_height),
+                Rgba([r, g, b, 255]),
+            );
+
+            let count = count.to_compact_string();
+            let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, &count);
+            draw_text(
+                img,
+                text_color,
+                x + bar_width.saturating_sub(tw) / 2,
+                baseline - bar_height - CROP_HEIGHT,
+                CARD_NAME_SCALE,
+                &count,
+            );
+        }
+
+        let cost = if cost == 7 {
+            CompactString::from("7+")
+        } else {
+            cost.to_compact_string()
+        };
+        let (tw, _) = drawing::text_size(CARD_NAME_SCALE, &*FONTS[0].0, &cost);
+        draw_text(
+            img,
+            text_color,
+            x + bar_width.saturating_sub(tw) / 2,
+            baseline,
+            CARD_NAME_SCALE,
+            &cost,
+        );
+    }
+}
+
+// The dust cost is only drawn with the stats panel, to leave other images unchanged.
 fn draw_deck_title(
     img: &mut RgbaImage,
     deck: &Deck,
     vertical: bool,
+    dust: bool,
 ) {
     let offset = get_class_icon(deck.class).map_or(MARGIN, |class_img| {
         let mut class_img = imageops::resize(
@@ -501,6 +1078,26 @@ fn draw_deck_title(
         HEADING_SCALE,
         &deck.title,
     );
+
+    if vertical || dust.not() {
+        return;
+    }
+
+    // Dust cost, right aligned, only if there is room left after the title.
+    let dust = format_compact!("{} Dust", deck.dust_cost().total());
+    let (title_w, _) = drawing::text_size(HEADING_SCALE, &*FONTS[0].0, &deck.title);
+    let (dust_w, _) = drawing::text_size(HEADING_SCALE, &*FONTS[0].0, &dust);
+
+    if offset + title_w + MARGIN + dust_w + MARGIN <= img.width() {
+        draw_text(
+            img,
+            [10, 10, 10, 255],
+            img.width() - (dust_w + MARGIN),
+            MARGIN,
+            HEADING_SCALE,
+            &dust,
+        );
+    }
 }
 
 fn draw_footer(
@@ -0,0 +1,324 @@
+use crate::{
+    authorization::has_blizzard_client_auth,
+    card::{self, SearchOptions},
+    card_details::Class,
+    deck::{Deck, Format, LookupOptions, encode_code, lookup},
+    hearth_sim::{fuzzy_search_hearth_sim, search_hearth_sim_by_name},
+    localization::Locale,
+};
+use anyhow::Result;
+use compact_str::{CompactString, ToCompactString};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    ops::Not,
+};
+
+pub struct ImportOptions<'s> {
+    list: &'s str,
+    class: Class,
+    locale: Locale,
+    format: Option<&'s str>,
+}
+
+impl<'s> ImportOptions<'s> {
+    #[must_use]
+    pub const fn import(
+        list: &'s str,
+        class: Class,
+    ) -> Self {
+        Self {
+            list,
+            class,
+            locale: Locale::enUS,
+            format: None,
+        }
+    }
+    #[must_use]
+    pub const fn with_locale(
+        self,
+        locale: Locale,
+    ) -> Self {
+        Self { locale, ..self }
+    }
+    #[must_use]
+    pub const fn with_format(
+        self,
+        format: Option<&'s str>,
+    ) -> Self {
+        Self { format, ..self }
+    }
+}
+
+/// Lines that could not be turned into a single card. Line numbers start at 1.
+pub enum ImportIssue {
+    Unresolved {
+        line: usize,
+        name: CompactString,
+        suggestion: Option<CompactString>,
+    },
+    /// The first match was used.
+    Ambiguous {
+        line: usize,
+        name: CompactString,
+        matches: usize,
+    },
+}
+impl Display for ImportIssue {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::Unresolved {
+                line,
+                name,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Line {line}: No card found named \"{name}\". Did you mean \"{suggestion}\"?"
+            ),
+            Self::Unresolved { line, name, .. } => {
+                write!(f, "Line {line}: No card found named \"{name}\".")
+            }
+            Self::Ambiguous {
+                line,
+                name,
+                matches,
+            } => write!(
+                f,
+                "Line {line}: \"{name}\" matches {matches} different cards. Used the first one."
+            ),
+        }
+    }
+}
+
+enum Resolution {
+    Found(usize),
+    Ambiguous(usize, usize),
+    NotFound(Option<CompactString>),
+}
+
+/// Builds a deck from a list of card names, one card per line, such as `2x Fireball`,
+/// `1 Ragnaros the Firelord`, or `(3) Frostbolt x2`. The game client's own export works too.
+pub fn import(opts: ImportOptions<'_>) -> Result<(Deck, Vec<ImportIssue>)> {
+    let mut title = None;
+    let mut cards = BTreeMap::<usize, usize>::new();
+    let mut sideboard_cards = Vec::<(usize, usize)>::new();
+    let mut issues = Vec::new();
+
+    // The card whose sideboard the following lines belong to.
+    let mut sideboard_owner = None;
+
+    let mut resolve_line = |line: usize, name: &str| match resolve(name, opts.class, opts.locale) {
+        Resolution::Found(id) => Some(id),
+        Resolution::Ambiguous(id, matches) => {
+            issues.push(ImportIssue::Ambiguous {
+                line,
+                name: name.into(),
+                matches,
+            });
+            Some(id)
+        }
+        Resolution::NotFound(suggestion) => {
+            issues.push(ImportIssue::Unresolved {
+                line,
+                name: name.into(),
+                suggestion,
+            });
+            None
+        }
+    };
+
+    for (idx, line) in opts.list.lines().enumerate() {
+        let line_no = idx + 1;
+
+        if let Some(t) = line.trim().strip_prefix("###") {
+            title = Some(t.trim().to_compact_string());
+            continue;
+        }
+
+        let content = line.trim().trim_start_matches('#').trim();
+        if content.is_empty() {
+            sideboard_owner = None;
+            continue;
+        }
+
+        if let Some(owner) = parse_sideboard_header(content, opts.locale) {
+            sideboard_owner = resolve_line(line_no, owner);
+            continue;
+        }
+
+        let Some((count, name)) = parse_line(content) else {
+            continue;
+        };
+        let Some(id) = resolve_line(line_no, name) else {
+            continue;
+        };
+
+        match sideboard_owner {
+            Some(owner) => sideboard_cards.extend(std::iter::repeat_n((id, owner), count)),
+            None => *cards.entry(id).or_default() += count,
+        }
+    }
+
+    anyhow::ensure!(
+        cards.is_empty().not(),
+        "No cards found in list. Lines should look like \"2x Fireball\"."
+    );
+
+    let format = opts
+        .format
+        .map(|f| Format::from(f.to_owned()))
+        .unwrap_or_default();
+    let cards = cards.into_iter().collect::<Vec<_>>();
+    let code = encode_code(opts.class.hero_id(), &format, &cards, &sideboard_cards);
+
+    let mut deck = lookup(
+        LookupOptions::lookup(&code)
+            .with_locale(opts.locale)
+            .with_custom_format(opts.format)
+            .offline(has_blizzard_client_auth().not()),
+    )?;
+
+    if let Some(title) = title {
+        deck.title = title;
+    }
+
+    Ok((deck, issues))
+}
+
+fn resolve(
+    name: &str,
+    class: Class,
+    locale: Locale,
+) -> Resolution {
+    let from_ids = |ids: Vec<usize>| match ids.as_slice() {
+        [] => None,
+        [id] => Some(Resolution::Found(*id)),
+        [id, ..] => Some(Resolution::Ambiguous(*id, ids.len())),
+    };
+
+    if let Some(res) = from_ids(search_hearth_sim_by_name(name, class)) {
+        return res;
+    }
+
+    let fuzzed = fuzzy_search_hearth_sim(name);
+    if let Some((fuzzed, score)) = &fuzzed
+        && *score >= 150 // arbitrary. Same as card search.
+        && let Some(res) = from_ids(search_hearth_sim_by_name(fuzzed, class))
+    {
+        return res;
+    }
+
+    // Names in other languages are only found through Blizzard's API.
+    if has_blizzard_client_auth()
+        && let Ok(found) = card::lookup(SearchOptions::search_for(name).with_locale(locale))
+    {
+        let ids = found
+            .filter(|c| c.name.to_lowercase() == name.to_lowercase())
+            .filter(|c| c.class.is_empty() || c.class.contains(class))
+            .map(|c| c.id)
+            .collect();
+        if let Some(res) = from_ids(ids) {
+            return res;
+        }
+    }
+
+    Resolution::NotFound(fuzzed.map(|(s, _)| s))
+}
+
+// `Sideboard: E.T.C., Band Manager`, in English or the list's locale.
+fn parse_sideboard_header(
+    line: &str,
+    locale: Locale,
+) -> Option<&str> {
+    let (label, owner) = line.split_once(':')?;
+    let label = label.trim();
+
+    [Locale::enUS, locale]
+        .iter()
+        .any(|l| l.sideboard_label().eq_ignore_ascii_case(label))
+        .then(|| owner.trim())
+}
+
+// Count first (`2x Fireball`, `2 Fireball`, `# 2x (3) Fireball`) or last (`Fireball x2`).
+// A mana cost in parentheses may come before the name. Returns None for other lines.
+fn parse_line(line: &str) -> Option<(usize, &str)> {
+    use nom::{
+        Parser,
+        branch::alt,
+        character::complete::{char, digit1, one_of, space1},
+        combinator::{opt, recognize},
+        sequence::{delimited, preceded, terminated},
+    };
+
+    fn count(input: &str) -> nom::IResult<&str, usize, ()> {
+        digit1.map_res(str::parse::<usize>).parse(input)
+    }
+    fn times(input: &str) -> nom::IResult<&str, char, ()> {
+        one_of("xX×").parse(input)
+    }
+
+    let (name, (prefix, cost)) = (
+        opt(terminated(count, (opt(times), space1))),
+        opt(terminated(
+            recognize(delimited(char('('), digit1, char(')'))),
+            space1,
+        )),
+    )
+        .parse(line)
+        .ok()?;
+
+    let (name, suffix) = name
+        .rsplit_once(' ')
+        .and_then(|(rest, last)| {
+            let (tail, n) = alt((preceded(times, count), terminated(count, times)))
+                .parse(last)
+                .ok()?;
+            tail.is_empty().then_some((rest.trim_end(), Some(n)))
+        })
+        .unwrap_or((name, None));
+
+    let count = prefix.or(suffix).or(cost.map(|_| 1))?;
+
+    (count > 0 && name.is_empty().not()).then_some((count, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test {
+        ($name:ident, $line:literal, $expected:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse_line($line), $expected);
+            }
+        };
+    }
+
+    test!(count_first, "2x Fireball", Some((2, "Fireball")));
+    test!(
+        count_no_x,
+        "1 Ragnaros the Firelord",
+        Some((1, "Ragnaros the Firelord"))
+    );
+    test!(
+        cost_and_count_last,
+        "(3) Frostbolt x2",
+        Some((2, "Frostbolt"))
+    );
+    test!(
+        client_format,
+        "2x (1) Sir Finley, Sea Guide",
+        Some((2, "Sir Finley, Sea Guide"))
+    );
+    test!(
+        cost_only,
+        "(5) Zilliax Deluxe 3000",
+        Some((1, "Zilliax Deluxe 3000"))
+    );
+    test!(no_count, "Class: Mage", None);
+    test!(zero_count, "0x Fireball", None);
+}
@@ -0,0 +1,237 @@
+#![allow(clippy::cast_precision_loss)]
+
+use crate::deck::Deck;
+use anyhow::Result;
+use itertools::Itertools;
+use std::ops::Not;
+
+// Curve out masks grow as 2^turns.
+const MAX_CURVE_TURNS: u8 = 10;
+
+#[derive(Clone, Copy, Default)]
+pub enum Mulligan {
+    /// Keep whatever the opening hand is.
+    #[default]
+    Keep,
+    /// Throw back the whole opening hand when it has none of the wanted cards.
+    Hunt,
+}
+
+/// Draw odds for a deck. Turns count from 1, and turn 0 is the opening hand after the
+/// mulligan. The Coin is not counted as a card.
+pub struct Odds<'d> {
+    deck: &'d Deck,
+    going_first: bool,
+    mulligan: Mulligan,
+}
+
+impl<'d> Odds<'d> {
+    #[must_use]
+    pub const fn new(deck: &'d Deck) -> Self {
+        Self {
+            deck,
+            going_first: true,
+            mulligan: Mulligan::Keep,
+        }
+    }
+    #[must_use]
+    pub const fn going_first(
+        self,
+        going_first: bool,
+    ) -> Self {
+        Self {
+            going_first,
+            ..self
+        }
+    }
+    #[must_use]
+    pub const fn with_mulligan(
+        self,
+        mulligan: Mulligan,
+    ) -> Self {
+        Self { mulligan, ..self }
+    }
+
+    const fn hand_size(&self) -> usize {
+        if self.going_first { 3 } else { 4 }
+    }
+
+    /// Copies in the deck of the cards whose names contain any of `cards`, ignoring case.
+    ///
+    /// Fails if one of `cards` matches nothing, as it is most likely a typo.
+    pub fn copies(
+        &self,
+        cards: &[&str],
+    ) -> Result<usize> {
+        let cards = cards.iter().map(|c| c.to_lowercase()).collect_vec();
+
+        if let Some(missing) = cards.iter().find(|c| {
+            self.deck
+                .cards
+                .iter()
+                .all(|card| card.name.to_lowercase().contains(c.as_str()).not())
+        }) {
+            anyhow::bail!("No card in the deck matches \"{missing}\".");
+        }
+
+        Ok(self
+            .deck
+            .cards
+            .iter()
+            .filter(|card| {
+                let name = card.name.to_lowercase();
+                cards.iter().any(|c| name.contains(c.as_str()))
+            })
+            .count())
+    }
+
+    /// Chance to have at least one of `cards` in the opening hand.
+    pub fn opening_hand(
+        &self,
+        cards: &[&str],
+    ) -> Result<f64> {
+        self.by_turn(cards, 0)
+    }
+
+    /// Chance to have drawn at least one of `cards` by the given turn.
+    pub fn by_turn(
+        &self,
+        cards: &[&str],
+        turn: usize,
+    ) -> Result<f64> {
+        Ok(chance_by_turn(
+            self.deck.cards.len(),
+            self.copies(cards)?,
+            self.hand_size(),
+            turn,
+            self.mulligan,
+        ))
+    }
+
+    /// Chance to have a card costing 1 on turn 1, a card costing 2 on turn 2, and so on until
+    /// `turns`, which is capped at 10. Assumes the opening hand is kept.
+    #[must_use]
+    pub fn curve_out(
+        &self,
+        turns: u8,
+    ) -> f64 {
+        let costs = (1..=turns.min(MAX_CURVE_TURNS))
+            .map(|cost| self.deck.cards.iter().filter(|c| c.cost == cost).count())
+            .collect_vec();
+
+        curve_out_chance(self.deck.cards.len(), &costs, self.hand_size())
+    }
+}
+
+// Chance that `draws` cards from a deck of `size` cards miss all `hits` wanted cards.
+fn miss(
+    size: usize,
+    hits: usize,
+    draws: usize,
+) -> f64 {
+    if hits == 0 {
+        return 1.0;
+    }
+    if draws + hits > size {
+        return 0.0;
+    }
+    (0..draws)
+        .map(|i| (size - hits - i) as f64 / (size - i) as f64)
+        .product()
+}
+
+fn chance_by_turn(
+    size: usize,
+    copies: usize,
+    hand: usize,
+    turn: usize,
+    mulligan: Mulligan,
+) -> f64 {
+    let miss = match mulligan {
+        Mulligan::Keep => miss(size, copies, hand + turn),
+        // The thrown back cards cannot be drawn again as replacements.
+        Mulligan::Hunt => {
+            let rest = size.saturating_sub(hand);
+            miss(size, copies, hand) * miss(rest, copies, hand) * miss(rest, copies, turn)
+        }
+    };
+    1.0 - miss
+}
+
+// `costs[t]` is the number of cards costing `t + 1`. Goes over the draws one by one, keeping
+// the chance of every set of costs seen so far. Further copies of a seen cost do not matter,
+// so the set is enough to know what is left in the deck.
+fn curve_out_chance(
+    size: usize,
+    costs: &[usize],
+    hand: usize,
+) -> f64 {
+    let mut states = vec![0.0; 1 << costs.len()];
+    states[0] = 1.0;
+
+    for drawn in 0..(hand + costs.len()).min(size) {
+        let left = (size - drawn) as f64;
+        let mut next = vec![0.0; states.len()];
+
+        for (seen, &p) in states.iter().enumerate().filter(|(_, p)| **p > 0.0) {
+            let mut rest = left;
+            for (t, &copies) in costs.iter().enumerate() {
+                if seen & 1 << t == 0 {
+                    next[seen | 1 << t] += p * copies as f64 / left;
+                    rest -= copies as f64;
+                }
+            }
+            next[seen] += p * rest / left;
+        }
+
+        // On turn t, the cost t card must be among the `hand + t` drawn cards.
+        if let Some(t) = (drawn + 1).checked_sub(hand).filter(|&t| t > 0) {
+            next.iter_mut()
+                .enumerate()
+                .filter(|(seen, _)| seen & 1 << (t - 1) == 0)
+                .for_each(|(_, p)| *p = 0.0);
+        }
+
+        states = next;
+    }
+
+    states.iter().sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(
+        a: f64,
+        b: f64,
+    ) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn opening_hand() {
+        // 1 - (28 * 27 * 26) / (30 * 29 * 28)
+        assert_close(
+            chance_by_turn(30, 2, 3, 0, Mulligan::Keep),
+            1.0 - 702.0 / 870.0,
+        );
+        assert_close(chance_by_turn(30, 0, 3, 5, Mulligan::Keep), 0.0);
+        assert_close(chance_by_turn(30, 30, 3, 0, Mulligan::Keep), 1.0);
+        assert!(
+            chance_by_turn(30, 2, 4, 0, Mulligan::Hunt)
+                > chance_by_turn(30, 2, 4, 0, Mulligan::Keep)
+        );
+    }
+
+    #[test]
+    fn curve_out() {
+        // with a single turn, curving out is drawing a 1 drop by turn 1.
+        assert_close(
+            curve_out_chance(30, &[4], 3),
+            chance_by_turn(30, 4, 3, 1, Mulligan::Keep),
+        );
+        assert_close(curve_out_chance(30, &[4, 0], 3), 0.0);
+        assert_close(curve_out_chance(30, &[], 3), 1.0);
+    }
+}
@@ -0,0 +1,400 @@
+#![allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+
+use crate::{
+    deck::Deck,
+    localization::{Locale, Localize},
+};
+use compact_str::CompactString;
+use std::{
+    fmt::{Display, Write},
+    ops::Not,
+};
+
+pub const SIMULATED_TURNS: usize = 4;
+// Turn 4 with the Coin.
+const MAX_MANA: usize = SIMULATED_TURNS + 1;
+
+pub enum MulliganPolicy {
+    /// Keep cards costing this much or less.
+    MaxCost(u8),
+    /// Keep cards whose names contain any of these, ignoring case.
+    KeepList(Vec<CompactString>),
+}
+
+pub struct SimulationOptions<'d> {
+    deck: &'d Deck,
+    policy: MulliganPolicy,
+    tracked: Vec<CompactString>,
+    going_first: bool,
+    runs: usize,
+    seed: u64,
+}
+
+impl<'d> SimulationOptions<'d> {
+    /// Defaults to 10,000 games going first, keeping cards that cost 2 or less.
+    #[must_use]
+    pub const fn simulate(deck: &'d Deck) -> Self {
+        Self {
+            deck,
+            policy: MulliganPolicy::MaxCost(2),
+            tracked: Vec::new(),
+            going_first: true,
+            runs: 10_000,
+            seed: 0,
+        }
+    }
+    #[must_use]
+    pub fn with_policy(
+        self,
+        policy: MulliganPolicy,
+    ) -> Self {
+        Self { policy, ..self }
+    }
+    /// Cards to report how often they are held, up to 64. Names are matched like
+    /// `MulliganPolicy::KeepList`.
+    #[must_use]
+    pub fn tracking(
+        self,
+        tracked: Vec<CompactString>,
+    ) -> Self {
+        Self { tracked, ..self }
+    }
+    #[must_use]
+    pub fn going_first(
+        self,
+        going_first: bool,
+    ) -> Self {
+        Self {
+            going_first,
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_runs(
+        self,
+        runs: usize,
+    ) -> Self {
+        Self { runs, ..self }
+    }
+    #[must_use]
+    pub fn with_seed(
+        self,
+        seed: u64,
+    ) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HeldCard {
+    pub name: CompactString,
+    /// Games where the card was in hand after the mulligan.
+    pub after_mulligan: usize,
+    /// Games where the card was drawn by the last simulated turn.
+    pub by_last_turn: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SimulationReport {
+    pub runs: usize,
+    pub going_first: bool,
+    /// `mana_spent[t][m]` is the number of games where exactly `m` mana was spent on turn
+    /// `t + 1`.
+    pub mana_spent: [[usize; MAX_MANA + 1]; SIMULATED_TURNS],
+    pub held: Vec<HeldCard>,
+}
+
+impl SimulationReport {
+    /// Average mana spent on each turn.
+    #[must_use]
+    pub fn average_mana(&self) -> [f64; SIMULATED_TURNS] {
+        self.mana_spent.map(|turn| {
+            let total = turn
+                .iter()
+                .enumerate()
+                .map(|(mana, games)| mana * games)
+                .sum::<usize>();
+            total as f64 / self.runs.max(1) as f64
+        })
+    }
+}
+
+impl Localize for SimulationReport {
+    fn in_locale(
+        &self,
+        _: Locale,
+    ) -> impl Display {
+        let mut buffer = String::new();
+        let percent = |games: usize| games as f64 * 100.0 / self.runs.max(1) as f64;
+
+        writeln!(
+            buffer,
+            "{} games going {}.\n",
+            self.runs,
+            if self.going_first { "first" } else { "second" }
+        )
+        .ok();
+
+        write!(buffer, "Mana spent").ok();
+        for mana in 0..=MAX_MANA {
+            write!(buffer, "{mana:>7}").ok();
+        }
+        writeln!(buffer, "{:>9}", "Average").ok();
+
+        for (turn, (spent, average)) in self.mana_spent.iter().zip(self.average_mana()).enumerate()
+        {
+            write!(buffer, "Turn {:<5}", turn + 1).ok();
+            for games in spent {
+                write!(buffer, "{:>6.1}%", percent(*games)).ok();
+            }
+            writeln!(buffer, "{average:>9.2}").ok();
+        }
+
+        if self.held.is_empty().not() {
+            writeln!(
+                buffer,
+                "\nHeld after mulligan, and by turn {SIMULATED_TURNS}:"
+            )
+            .ok();
+            for card in &self.held {
+                writeln!(
+                    buffer,
+                    "{:>6.1}% {:>6.1}%  {}",
+                    percent(card.after_mulligan),
+                    percent(card.by_last_turn),
+                    card.name
+                )
+                .ok();
+            }
+        }
+
+        buffer
+    }
+}
+
+/// Plays the first turns of many games with the deck, using a fixed seed so the same options
+/// always give the same report.
+///
+/// Each turn plays the cards that spend the most mana, and the Coin only when it lets more
+/// mana be spent. Card effects are ignored.
+#[must_use]
+pub fn simulate(opts: SimulationOptions<'_>) -> SimulationReport {
+    let matches = |name: &str, list: &[CompactString]| {
+        let name = name.to_lowercase();
+        list.iter()
+            .any(|l| name.contains(l.to_lowercase().as_str()))
+    };
+
+    let cards = opts
+        .deck
+        .cards
+        .iter()
+        .map(|card| SimCard {
+            cost: card.cost,
+            keep: match &opts.policy {
+                MulliganPolicy::MaxCost(cost) => card.cost <= *cost,
+                MulliganPolicy::KeepList(list) => matches(&card.name, list),
+            },
+            tracked: opts
+                .tracked
+                .iter()
+                .take(64)
+                .enumerate()
+                .filter(|(_, t)| matches(&card.name, std::slice::from_ref(t)))
+                .fold(0, |acc, (i, _)| acc | 1 << i),
+        })
+        .collect::<Vec<_>>();
+
+    let counts = run(
+        &cards,
+        opts.going_first,
+        opts.runs,
+        opts.seed,
+        opts.tracked.len(),
+    );
+
+    SimulationReport {
+        runs: opts.runs,
+        going_first: opts.going_first,
+        mana_spent: counts.mana_spent,
+        held: opts
+            .tracked
+            .into_iter()
+            .zip(counts.held)
+            .map(|(name, (after_mulligan, by_last_turn))| HeldCard {
+                name,
+                after_mulligan,
+                by_last_turn,
+            })
+            .collect(),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SimCard {
+    cost: u8,
+    keep: bool,
+    // bit `i` is set when the card is the `i`th tracked card.
+    tracked: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Counts {
+    mana_spent: [[usize; MAX_MANA + 1]; SIMULATED_TURNS],
+    held: Vec<(usize, usize)>,
+}
+
+fn run(
+    cards: &[SimCard],
+    going_first: bool,
+    runs: usize,
+    seed: u64,
+    tracked: usize,
+) -> Counts {
+    let mut rng = SplitMix64(seed);
+    let mut counts = Counts {
+        mana_spent: [[0; MAX_MANA + 1]; SIMULATED_TURNS],
+        held: vec![(0, 0); tracked.min(64)],
+    };
+    let hand_size = if going_first { 3 } else { 4 };
+    let tracked_in = |cards: &[SimCard]| cards.iter().fold(0, |acc, c| acc | c.tracked);
+
+    for _ in 0..runs {
+        let mut deck = cards.to_vec();
+        rng.shuffle(&mut deck);
+
+        // Mulligan. Thrown back cards are shuffled in after the replacements are drawn.
+        let mut rest = deck.split_off(hand_size.min(deck.len()));
+        let (mut hand, thrown): (Vec<_>, Vec<_>) = deck.into_iter().partition(|c| c.keep);
+        hand.extend(rest.drain(..thrown.len().min(rest.len())));
+        rest.extend(thrown);
+        rng.shuffle(&mut rest);
+
+        let after_mulligan = tracked_in(&hand);
+        let mut drawn = after_mulligan;
+
+        let mut coin = going_first.not();
+        let mut rest = rest.into_iter();
+
+        for (turn, mana_spent) in counts.mana_spent.iter_mut().enumerate() {
+            if let Some(card) = rest.next() {
+                drawn |= card.tracked;
+                hand.push(card);
+            }
+
+            let mana = turn + 1;
+            let (mut spent, mut played) = best_play(&hand, mana);
+            if coin {
+                let (with_coin, coin_played) = best_play(&hand, mana + 1);
+                if with_coin > spent {
+                    (spent, played, coin) = (with_coin, coin_played, false);
+                }
+            }
+            mana_spent[spent] += 1;
+
+            let mut idx = 0;
+            hand.retain(|_| {
+                idx += 1;
+                played & 1 << (idx - 1) == 0
+            });
+        }
+
+        for (i, (held, by_last_turn)) in counts.held.iter_mut().enumerate() {
+            *held += usize::from(after_mulligan & 1 << i != 0);
+            *by_last_turn += usize::from(drawn & 1 << i != 0);
+        }
+    }
+
+    counts
+}
+
+// The most mana the hand can spend, and the cards (as bits of their position) to do so.
+fn best_play(
+    hand: &[SimCard],
+    mana: usize,
+) -> (usize, u32) {
+    // hands hold at most 10 cards.
+    let hand = &hand[..hand.len().min(10)];
+
+    (0..1u32 << hand.len())
+        .map(|played| {
+            let spent = hand
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| played & 1 << i != 0)
+                .map(|(_, c)| usize::from(c.cost))
+                .sum::<usize>();
+            (spent, played)
+        })
+        .filter(|(spent, _)| *spent <= mana)
+        .max_by_key(|(spent, played)| (*spent, std::cmp::Reverse(*played)))
+        .unwrap_or_default()
+}
+
+// https://prng.di.unimi.it/splitmix64.c
+// Small, and the same on every platform and version, unlike most seeded generators.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Fisher-Yates
+    fn shuffle<T>(
+        &mut self,
+        items: &mut [T],
+    ) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(costs: &[u8]) -> Vec<SimCard> {
+        costs
+            .iter()
+            .enumerate()
+            .map(|(i, &cost)| SimCard {
+                cost,
+                keep: cost <= 2,
+                tracked: u64::from(i == 0),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splitmix64() {
+        let mut rng = SplitMix64(0);
+        assert_eq!(rng.next(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn same_seed_same_report() {
+        let deck = cards(&[1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8].repeat(2));
+
+        assert_eq!(run(&deck, true, 500, 7, 1), run(&deck, true, 500, 7, 1));
+        assert_ne!(run(&deck, true, 500, 7, 1), run(&deck, true, 500, 8, 1));
+    }
+
+    #[test]
+    fn spends_all_mana() {
+        let counts = run(&cards(&[1; 30]), false, 100, 0, 1);
+
+        // The Coin is used on turn 1, and the hand runs out on turn 4.
+        assert_eq!(counts.mana_spent[0][2], 100);
+        assert_eq!(counts.mana_spent[1][2], 100);
+        assert_eq!(counts.mana_spent[2][3], 100);
+        assert_eq!(counts.mana_spent[3][1], 100);
+        assert!(counts.held[0].1 >= counts.held[0].0);
+    }
+}
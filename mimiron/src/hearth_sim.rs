@@ -1,5 +1,10 @@
-use crate::{AGENT, card_details::Rarity};
+use crate::{
+    AGENT,
+    card::Card,
+    card_details::{CORE_SET_ID, CardType, Class, MinionType, Rarity, RuneCost, SpellSchool},
+};
 use compact_str::{CompactString, format_compact};
+use itertools::Itertools;
 use nucleo_matcher::{
     Config, Matcher,
     pattern::{CaseMatching, Normalization, Pattern},
@@ -8,6 +13,7 @@ use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
 use serde::Deserialize;
 use std::{
     collections::HashMap,
+    ops::Not,
     time::{Duration, Instant},
 };
 
@@ -15,7 +21,7 @@ use std::{
 // Uses https://hearthstonejson.com data for back up if needed.
 
 type HearthSim = HashMap<usize, HearthSimData>;
-const REFRESH_RATE: Duration = Duration::from_secs(86400 * 7); // a week
+const REFRESH_RATE: Duration = Duration::from_hours(24 * 7); // a week
 
 static HEARTH_SIM_IDS: RwLock<Option<(HearthSim, Instant)>> = RwLock::new(None);
 
@@ -26,7 +32,7 @@ fn inner_get_hearth_sim_ids() -> HearthSim {
         .and_then(|mut res| res.body_mut().read_json::<Vec<HearthSimData>>())
         .map(|v| {
             v.into_iter()
-                .filter(|d| d.cost.is_some())
+                .filter(|d| d.cost.is_some() || d.card_type == "HERO")
                 .map(|d| (d.dbf_id, d))
                 .collect::<HashMap<_, _>>()
         })
@@ -59,6 +65,42 @@ struct HearthSimData {
     rarity: CompactString,
     #[serde(default)]
     collectible: bool,
+    #[serde(default)]
+    set: CompactString,
+
+    // Full card data for offline decks.
+    #[serde(default, rename = "type")]
+    card_type: CompactString,
+    card_class: Option<CompactString>,
+    classes: Option<Vec<CompactString>>,
+    #[serde(default)]
+    text: CompactString,
+    #[serde(default)]
+    flavor: CompactString,
+
+    attack: Option<u8>,
+    health: Option<u8>,
+    durability: Option<u8>,
+    armor: Option<u8>,
+
+    #[serde(default)]
+    races: Vec<CompactString>,
+    spell_school: Option<CompactString>,
+    rune_cost: Option<RuneCost>,
+    #[serde(default)]
+    mechanics: Vec<CompactString>,
+}
+impl HearthSimData {
+    fn rarity(&self) -> Rarity {
+        match self.rarity.as_str() {
+            "LEGENDARY" => Rarity::Legendary,
+            "EPIC" => Rarity::Epic,
+            "RARE" => Rarity::Rare,
+            "COMMON" => Rarity::Common,
+            "FREE" => Rarity::Free,
+            _ => Rarity::Noncollectible,
+        }
+    }
 }
 
 pub fn get_hearth_sim_crop_image(id: usize) -> Option<CompactString> {
@@ -68,16 +110,162 @@ pub fn get_hearth_sim_crop_image(id: usize) -> Option<CompactString> {
 }
 
 pub fn get_hearth_sim_details(id: usize) -> Option<(CompactString, u8, Rarity)> {
-    get_hearth_sim_ids().get(&id).map(|c| {
-        let rarity = match c.rarity.as_str() {
-            "LEGENDARY" => Rarity::Legendary,
-            "EPIC" => Rarity::Epic,
-            "RARE" => Rarity::Rare,
-            "COMMON" => Rarity::Common,
-            "FREE" => Rarity::Free,
-            _ => Rarity::Noncollectible,
-        };
-        (c.name.clone(), c.cost.unwrap(), rarity)
+    get_hearth_sim_ids()
+        .get(&id)
+        .map(|c| (c.name.clone(), c.cost.unwrap_or_default(), c.rarity()))
+}
+
+// Firestone and HearthSim name cards by their string id. Looks up several at once, in a
+// single pass over the data. Unknown ids are left out.
+pub fn get_hearth_sim_dbf_ids<'a>(
+    card_ids: impl IntoIterator<Item = &'a str>
+) -> HashMap<&'a str, usize> {
+    let mut ids = card_ids
+        .into_iter()
+        .map(|id| (id, None))
+        .collect::<HashMap<_, Option<usize>>>();
+    for c in get_hearth_sim_ids().values() {
+        if let Some(dbf_id) = ids.get_mut(c.id.as_str()) {
+            *dbf_id = Some(c.dbf_id);
+        }
+    }
+
+    ids.into_iter()
+        .filter_map(|(id, dbf_id)| Some((id, dbf_id?)))
+        .collect()
+}
+
+// Tourists can be put in another class's deck. Blizzard's API does not mark them.
+pub fn is_hearth_sim_tourist(id: usize) -> bool {
+    get_hearth_sim_ids()
+        .get(&id)
+        .is_some_and(|c| c.mechanics.iter().any(|m| m == "TOURIST"))
+}
+
+// Full card from HearthSim data, without calling Blizzard's servers. English only.
+pub fn get_hearth_sim_card(id: usize) -> Option<Card> {
+    let data = get_hearth_sim_ids();
+    let c = data.get(&id)?;
+
+    let card_type = match c.card_type.as_str() {
+        "HERO" => CardType::Hero {
+            armor: c.armor.unwrap_or_default(),
+        },
+        "MINION" => CardType::Minion {
+            attack: c.attack.unwrap_or_default(),
+            health: c.health.unwrap_or_default(),
+            minion_types: c.races.iter().filter_map(|r| minion_type(r)).collect(),
+        },
+        "SPELL" => CardType::Spell {
+            school: c.spell_school.as_deref().and_then(spell_school),
+        },
+        "WEAPON" => CardType::Weapon {
+            attack: c.attack.unwrap_or_default(),
+            durability: c.durability.unwrap_or_default(),
+        },
+        "LOCATION" => CardType::Location {
+            durability: c.health.or(c.durability).unwrap_or_default(),
+        },
+        "HERO_POWER" => CardType::HeroPower,
+        _ => CardType::Unknown,
+    };
+
+    // HearthSim text has formatting markers that Blizzard's API does not.
+    let text = c
+        .text
+        .trim_start_matches("[x]")
+        .replace(['$', '#'], "")
+        .replace('\n', " ");
+
+    Some(Card {
+        id,
+        // HearthSim's set names do not match Blizzard's set IDs. Core matters for crafting.
+        set: if c.set == "CORE" { CORE_SET_ID } else { 0 },
+        name: c.name.clone(),
+        class: c
+            .classes
+            .iter()
+            .flatten()
+            .chain(c.card_class.as_ref())
+            .filter_map(|s| class(s))
+            .collect(),
+        cost: c.cost.unwrap_or_default(),
+        rune_cost: c.rune_cost.filter(|r| r.count() > 0),
+        card_type,
+        rarity: c.rarity(),
+        faction: None,
+        text: text.into(),
+        image: format_compact!(
+            "https://art.hearthstonejson.com/v1/render/latest/enUS/512x/{}.png",
+            c.id
+        ),
+        crop_image: Some(format_compact!(
+            "https://art.hearthstonejson.com/v1/tiles/{}.png",
+            c.id
+        )),
+        flavor_text: c.flavor.clone(),
+        cosmetic: false,
+    })
+}
+
+fn class(s: &str) -> Option<Class> {
+    Some(match s {
+        "DEATHKNIGHT" => Class::DeathKnight,
+        "DEMONHUNTER" => Class::DemonHunter,
+        "DRUID" => Class::Druid,
+        "HUNTER" => Class::Hunter,
+        "MAGE" => Class::Mage,
+        "PALADIN" => Class::Paladin,
+        "PRIEST" => Class::Priest,
+        "ROGUE" => Class::Rogue,
+        "SHAMAN" => Class::Shaman,
+        "WARLOCK" => Class::Warlock,
+        "WARRIOR" => Class::Warrior,
+        _ => return None, // Neutral
+    })
+}
+
+fn minion_type(s: &str) -> Option<MinionType> {
+    Some(match s {
+        "BLOODELF" => MinionType::BloodElf,
+        "DRAENEI" => MinionType::Draenei,
+        "DWARF" => MinionType::Dwarf,
+        "GNOME" => MinionType::Gnome,
+        "HUMAN" => MinionType::Human,
+        "NIGHTELF" => MinionType::NightElf,
+        "ORC" => MinionType::Orc,
+        "TAUREN" => MinionType::Tauren,
+        "TROLL" => MinionType::Troll,
+        "UNDEAD" => MinionType::Undead,
+        "MURLOC" => MinionType::Murloc,
+        "DEMON" => MinionType::Demon,
+        "MECHANICAL" => MinionType::Mech,
+        "ELEMENTAL" => MinionType::Elemental,
+        "BEAST" => MinionType::Beast,
+        "TOTEM" => MinionType::Totem,
+        "PIRATE" => MinionType::Pirate,
+        "DRAGON" => MinionType::Dragon,
+        "ALL" => MinionType::All,
+        "QUILBOAR" => MinionType::Quilboar,
+        "HALFORC" => MinionType::HalfOrc,
+        "NAGA" => MinionType::Naga,
+        "OLDGOD" => MinionType::OldGod,
+        "PANDAREN" => MinionType::Pandaren,
+        "GRONN" => MinionType::Gronn,
+        _ => return None,
+    })
+}
+
+fn spell_school(s: &str) -> Option<SpellSchool> {
+    Some(match s {
+        "ARCANE" => SpellSchool::Arcane,
+        "FIRE" => SpellSchool::Fire,
+        "FROST" => SpellSchool::Frost,
+        "NATURE" => SpellSchool::Nature,
+        "HOLY" => SpellSchool::Holy,
+        "SHADOW" => SpellSchool::Shadow,
+        "FEL" => SpellSchool::Fel,
+        _ => return None,
     })
 }
 
@@ -99,6 +287,40 @@ pub fn validate_id(input_id: usize) -> usize {
         .unwrap_or(input_id)
 }
 
+/// Cards named exactly `name` that `class` can use. Collectible cards are preferred,
+/// and reprints with the same text are collapsed into the oldest id.
+pub fn search_hearth_sim_by_name(
+    name: &str,
+    class: Class,
+) -> Vec<usize> {
+    let data = get_hearth_sim_ids();
+
+    let fits_class = |d: &HearthSimData| match &d.classes {
+        Some(classes) => classes.iter().any(|c| self::class(c) == Some(class)),
+        None => d
+            .card_class
+            .as_deref()
+            .and_then(self::class)
+            .is_none_or(|c| c == class),
+    };
+
+    let matches = data
+        .values()
+        .filter(|d| d.name.eq_ignore_ascii_case(name.trim()) && fits_class(d))
+        .collect::<Vec<_>>();
+
+    let any_collectible = matches.iter().any(|d| d.collectible);
+
+    matches
+        .into_iter()
+        .filter(|d| d.collectible || any_collectible.not())
+        .sorted_by_key(|d| d.count_as_copy_of_dbf_id.unwrap_or(d.dbf_id))
+        .unique_by(|d| &d.text)
+        .map(|d| d.count_as_copy_of_dbf_id.unwrap_or(d.dbf_id))
+        .dedup()
+        .collect()
+}
+
 pub fn fuzzy_search_hearth_sim(search_term: &str) -> Option<(CompactString, u32)> {
     // according to the docs doing these here is apparently horribly inefficient.
     // c'est la vie
@@ -2,14 +2,22 @@ use std::sync::LazyLock;
 
 mod authorization;
 pub mod bg;
+mod bg_meta;
 pub mod card;
 pub mod card_details;
 pub mod deck;
+mod deck_export;
 mod deck_image;
+mod deck_import;
+mod deck_odds;
+mod deck_sim;
 mod hearth_sim;
 pub mod keyword;
 pub mod localization;
 pub mod meta;
+mod meta_cards;
+mod meta_history;
+mod meta_matchups;
 pub mod news;
 mod text_utils;
 
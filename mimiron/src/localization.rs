@@ -1,4 +1,6 @@
+use crate::deck::Format;
 use anyhow::anyhow;
+use compact_str::{CompactString, format_compact};
 use std::{
     fmt::{Display, Formatter},
     str::FromStr,
@@ -58,6 +60,265 @@ impl Locale {
         }
     }
 
+    // Terms used in the deck text the game client copies to the clipboard.
+
+    pub(crate) const fn class_label(self) -> &'static str {
+        match self {
+            Self::deDE => "Klasse",
+            Self::enUS => "Class",
+            Self::esES | Self::esMX => "Clase",
+            Self::frFR | Self::itIT | Self::ptBR => "Classe",
+            Self::jaJP => "クラス",
+            Self::koKR => "직업",
+            Self::plPL => "Klasa",
+            Self::ruRU => "Класс",
+            Self::thTH => "คลาส",
+            Self::zhCN => "职业",
+            Self::zhTW => "職業",
+        }
+    }
+
+    pub(crate) const fn format_label(self) -> &'static str {
+        match self {
+            Self::deDE | Self::enUS | Self::frFR | Self::plPL => "Format",
+            Self::esES | Self::esMX | Self::itIT | Self::ptBR => "Formato",
+            Self::jaJP => "フォーマット",
+            Self::koKR => "대전 방식",
+            Self::ruRU => "Формат",
+            Self::thTH => "รูปแบบ",
+            Self::zhCN | Self::zhTW => "模式",
+        }
+    }
+
+    pub(crate) fn format_name(
+        self,
+        format: &Format,
+    ) -> &str {
+        match format {
+            Format::Standard => match self {
+                Self::deDE | Self::enUS | Self::frFR | Self::itIT => "Standard",
+                Self::esES | Self::esMX => "Estándar",
+                Self::jaJP => "スタンダード",
+                Self::koKR => "정규",
+                Self::plPL => "Standardowy",
+                Self::ptBR => "Padrão",
+                Self::ruRU => "Стандартный",
+                Self::thTH => "มาตรฐาน",
+                Self::zhCN => "标准",
+                Self::zhTW => "標準",
+            },
+            Format::Wild => match self {
+                Self::deDE | Self::enUS => "Wild",
+                Self::esES | Self::esMX => "Salvaje",
+                Self::frFR => "Libre",
+                Self::itIT => "Selvaggio",
+                Self::jaJP => "ワイルド",
+                Self::koKR => "야생",
+                Self::plPL => "Dziki",
+                Self::ptBR => "Livre",
+                Self::ruRU => "Вольный",
+                Self::thTH => "ไวลด์",
+                Self::zhCN => "狂野",
+                Self::zhTW => "開放",
+            },
+            Format::Classic => match self {
+                Self::deDE => "Klassisch",
+                Self::enUS => "Classic",
+                Self::esES | Self::esMX => "Clásico",
+                Self::frFR => "Classique",
+                Self::itIT => "Classico",
+                Self::jaJP => "クラシック",
+                Self::koKR => "클래식",
+                Self::plPL => "Klasyczny",
+                Self::ptBR => "Clássico",
+                Self::ruRU => "Классический",
+                Self::thTH => "คลาสสิก",
+                Self::zhCN => "经典",
+                Self::zhTW => "經典",
+            },
+            Format::Twist => match self {
+                Self::deDE
+                | Self::enUS
+                | Self::esES
+                | Self::esMX
+                | Self::frFR
+                | Self::itIT
+                | Self::plPL
+                | Self::ptBR => "Twist",
+                Self::jaJP => "ツイスト",
+                Self::koKR => "트위스트",
+                Self::ruRU => "Твист",
+                Self::thTH => "ทวิสต์",
+                Self::zhCN => "幻变",
+                Self::zhTW => "幻變",
+            },
+            Format::Custom(name) => name,
+        }
+    }
+
+    pub(crate) const fn sideboard_label(self) -> &'static str {
+        match self {
+            Self::deDE | Self::enUS => "Sideboard",
+            Self::esES | Self::esMX => "Banquillo",
+            Self::frFR => "Réserve",
+            Self::itIT => "Panchina",
+            Self::jaJP => "サイドボード",
+            Self::koKR => "사이드보드",
+            Self::plPL => "Ławka",
+            Self::ptBR => "Banco de reservas",
+            Self::ruRU => "Запас",
+            Self::thTH => "ไซด์บอร์ด",
+            Self::zhCN | Self::zhTW => "副牌",
+        }
+    }
+
+    pub(crate) const fn deck_instructions(self) -> &'static str {
+        match self {
+            Self::deDE => {
+                "Um dieses Deck zu benutzen, kopiert es in eure Zwischenablage und erstellt in Hearthstone ein neues Deck."
+            }
+            Self::enUS => {
+                "To use this deck, copy it to your clipboard and create a new deck in Hearthstone"
+            }
+            Self::esES | Self::esMX => {
+                "Para usar este mazo, cópialo en el portapapeles y crea un mazo nuevo en Hearthstone."
+            }
+            Self::frFR => {
+                "Pour utiliser ce deck, copiez-le dans le presse-papiers et créez un nouveau deck dans Hearthstone."
+            }
+            Self::itIT => {
+                "Per usare questo mazzo, copialo negli appunti e crea un nuovo mazzo in Hearthstone."
+            }
+            Self::jaJP => {
+                "このデッキを使うには、クリップボードにコピーしてからハースストーンで新規デッキを作成してください。"
+            }
+            Self::koKR => {
+                "이 덱을 사용하려면 클립보드에 복사한 후 하스스톤에서 새로운 덱을 만드세요."
+            }
+            Self::plPL => {
+                "Aby użyć tej talii, skopiuj ją do schowka i utwórz nową talię w Hearthstone."
+            }
+            Self::ptBR => {
+                "Para usar este deck, copie-o para a área de transferência e crie um novo deck em Hearthstone."
+            }
+            Self::ruRU => {
+                "Чтобы использовать эту колоду, скопируйте ее в буфер обмена и создайте новую колоду в Hearthstone."
+            }
+            Self::thTH => "หากต้องการใช้สำรับนี้ ให้คัดลอกไปยังคลิปบอร์ดแล้วสร้างสำรับใหม่ใน Hearthstone",
+            Self::zhCN => "要使用此套牌，请将其复制到剪贴板，然后在炉石传说中新建套牌。",
+            Self::zhTW => "若要使用此牌組，請將其複製到剪貼簿，然後在《爐石戰記》中建立新牌組。",
+        }
+    }
+
+    // Terms used in deck stats.
+
+    pub(crate) const fn mana_curve_label(self) -> &'static str {
+        match self {
+            Self::deDE => "Manakurve",
+            Self::enUS => "Mana Curve",
+            Self::esES | Self::esMX => "Curva de maná",
+            Self::frFR => "Courbe de mana",
+            Self::itIT => "Curva di mana",
+            Self::jaJP => "マナカーブ",
+            Self::koKR => "마나 곡선",
+            Self::plPL => "Krzywa many",
+            Self::ptBR => "Curva de mana",
+            Self::ruRU => "Кривая маны",
+            Self::thTH => "กราฟมานา",
+            Self::zhCN => "法力曲线",
+            Self::zhTW => "法力曲線",
+        }
+    }
+
+    pub(crate) const fn average_cost_label(self) -> &'static str {
+        match self {
+            Self::deDE => "Durchschnittliche Kosten",
+            Self::enUS => "Average Cost",
+            Self::esES => "Coste medio",
+            Self::esMX => "Costo promedio",
+            Self::frFR => "Coût moyen",
+            Self::itIT => "Costo medio",
+            Self::jaJP => "平均コスト",
+            Self::koKR => "평균 비용",
+            Self::plPL => "Średni koszt",
+            Self::ptBR => "Custo médio",
+            Self::ruRU => "Средняя стоимость",
+            Self::thTH => "ค่าร่ายเฉลี่ย",
+            Self::zhCN => "平均费用",
+            Self::zhTW => "平均費用",
+        }
+    }
+
+    pub(crate) const fn runes_label(self) -> &'static str {
+        match self {
+            Self::deDE => "Runen",
+            Self::enUS | Self::frFR => "Runes",
+            Self::esES | Self::esMX | Self::ptBR => "Runas",
+            Self::itIT => "Rune",
+            Self::jaJP => "ルーン",
+            Self::koKR => "룬",
+            Self::plPL => "Runy",
+            Self::ruRU => "Руны",
+            Self::thTH => "รูน",
+            Self::zhCN | Self::zhTW => "符文",
+        }
+    }
+
+    // Terms used in deck comparisons.
+
+    pub(crate) const fn core_cards_label(self) -> &'static str {
+        match self {
+            Self::deDE => "Kern",
+            Self::enUS => "Core",
+            Self::esES | Self::esMX | Self::ptBR => "Núcleo",
+            Self::frFR => "Base",
+            Self::itIT => "Nucleo",
+            Self::jaJP => "コア",
+            Self::koKR => "핵심",
+            Self::plPL => "Rdzeń",
+            Self::ruRU => "Основа",
+            Self::thTH => "แกนหลัก",
+            Self::zhCN | Self::zhTW => "核心",
+        }
+    }
+
+    pub(crate) const fn flex_cards_label(self) -> &'static str {
+        match self {
+            Self::deDE | Self::enUS => "Flex",
+            Self::esES | Self::esMX | Self::frFR => "Flexibles",
+            Self::itIT => "Flessibili",
+            Self::jaJP => "選択枠",
+            Self::koKR => "유동",
+            Self::plPL => "Elastyczne",
+            Self::ptBR => "Flexíveis",
+            Self::ruRU => "Гибкие",
+            Self::thTH => "ยืดหยุ่น",
+            Self::zhCN => "灵活",
+            Self::zhTW => "彈性",
+        }
+    }
+
+    pub(crate) fn consensus_title(
+        self,
+        decks: usize,
+    ) -> CompactString {
+        match self {
+            Self::deDE => format_compact!("Konsens aus {decks} Decks"),
+            Self::enUS => format_compact!("Consensus of {decks} Decks"),
+            Self::esES | Self::esMX => format_compact!("Consenso de {decks} mazos"),
+            Self::frFR => format_compact!("Consensus de {decks} decks"),
+            Self::itIT => format_compact!("Consenso di {decks} mazzi"),
+            Self::jaJP => format_compact!("{decks}個のデッキの共通リスト"),
+            Self::koKR => format_compact!("덱 {decks}개의 합의"),
+            Self::plPL => format_compact!("Konsensus {decks} talii"),
+            Self::ptBR => format_compact!("Consenso de {decks} baralhos"),
+            Self::ruRU => format_compact!("Консенсус {decks} колод"),
+            Self::thTH => format_compact!("สำรับรวมจาก {decks} สำรับ"),
+            Self::zhCN => format_compact!("{decks} 套卡组的共识"),
+            Self::zhTW => format_compact!("{decks} 副牌組的共識"),
+        }
+    }
+
     #[must_use]
     // fucking stupid that this is pub
     pub const fn golden(self) -> &'static str {
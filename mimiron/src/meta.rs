@@ -1,39 +1,217 @@
 use crate::{
     AGENT,
     card_details::Class,
-    deck::{Deck, Format, LookupOptions, lookup},
-    localization::Locale,
+    deck::{Deck, DeckDifference, Format, LookupOptions, canonical_ids, lookup},
+    localization::{Locale, Localize},
 };
 use anyhow::{Result, anyhow};
+use colored::Colorize;
 use compact_str::{CompactString, ToCompactString, format_compact};
 use itertools::Itertools;
-use serde::Deserialize;
-use std::time::Duration;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    ops::Not,
+    str::FromStr,
+    time::Duration,
+};
+
+pub use crate::{
+    meta_cards::{CardStats, card_stats},
+    meta_history::{MetaChanges, Mover, Since, SnapshotStore, TrendPoint},
+    meta_matchups::{Matchup, MatchupMatrix, matchup, matchup_matrix},
+};
 
 // Meta look up using Firestone's internal data.
 
-// Standard
-static STANDARD_DECKS_D_L: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/standard/legend-diamond/past-3/overview-from-hourly.gz.json";
-static STANDARD_DECKS_ALL: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/standard/all/past-3/overview-from-hourly.gz.json";
+/// Rank brackets Firestone publishes stats for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RankBracket {
+    Top2000Legend,
+    Legend,
+    LegendDiamond,
+    Diamond,
+    Platinum,
+    BronzeGold,
+    All,
+}
+impl RankBracket {
+    const fn slug(self) -> &'static str {
+        match self {
+            Self::Top2000Legend => "top-2000-legend",
+            Self::Legend => "legend",
+            Self::LegendDiamond => "legend-diamond",
+            Self::Diamond => "diamond",
+            Self::Platinum => "platinum",
+            Self::BronzeGold => "bronze-gold",
+            Self::All => "all",
+        }
+    }
+}
+impl Display for RankBracket {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.slug())
+    }
+}
+impl FromStr for RankBracket {
+    type Err = anyhow::Error;
 
-// Wild
-static WILD_DECKS_D_L: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/wild/legend-diamond/past-3/overview-from-hourly.gz.json";
-static WILD_DECKS_ALL: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/wild/all/past-3/overview-from-hourly.gz.json";
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase().replace([' ', '_'], "-");
+        Ok(match s.as_str() {
+            "top-2000-legend" | "top-2000" | "top" => Self::Top2000Legend,
+            "legend" => Self::Legend,
+            "legend-diamond" | "diamond-legend" => Self::LegendDiamond,
+            "diamond" => Self::Diamond,
+            "platinum" | "plat" => Self::Platinum,
+            "bronze-gold" | "gold" | "bronze" => Self::BronzeGold,
+            "all" => Self::All,
+            _ => anyhow::bail!("Not a valid rank bracket."),
+        })
+    }
+}
 
-// Twist
-static TWIST_DECKS_D_L: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/twist/legend-diamond/past-3/overview-from-hourly.gz.json";
-static TWIST_DECKS_ALL: &str = "https://static.zerotoheroes.com/api/constructed/stats/decks/twist/all/past-3/overview-from-hourly.gz.json";
+/// Time windows Firestone publishes stats for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimePeriod {
+    #[default]
+    Past3Days,
+    Past7Days,
+    Past20Days,
+    CurrentSeason,
+    /// Since the last patch.
+    CurrentPatch,
+}
+impl TimePeriod {
+    const fn slug(self) -> &'static str {
+        match self {
+            Self::Past3Days => "past-3",
+            Self::Past7Days => "past-7",
+            Self::Past20Days => "past-20",
+            Self::CurrentSeason => "current-season",
+            Self::CurrentPatch => "last-patch",
+        }
+    }
+}
+impl Display for TimePeriod {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.slug())
+    }
+}
+impl FromStr for TimePeriod {
+    type Err = anyhow::Error;
 
-#[derive(Deserialize, Clone)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase().replace([' ', '_'], "-");
+        Ok(match s.as_str() {
+            "past-3" | "3" => Self::Past3Days,
+            "past-7" | "7" => Self::Past7Days,
+            "past-20" | "20" => Self::Past20Days,
+            "current-season" | "season" => Self::CurrentSeason,
+            "last-patch" | "current-patch" | "patch" => Self::CurrentPatch,
+            _ => anyhow::bail!("Not a valid time period."),
+        })
+    }
+}
+
+/// Which of Firestone's stats to use. A plain `Format` works wherever a `MetaQuery` is taken.
+#[derive(Clone)]
+pub struct MetaQuery {
+    pub(crate) format: Format,
+    pub(crate) rank: Option<RankBracket>,
+    pub(crate) period: TimePeriod,
+    pub(crate) min_games: Option<u32>,
+}
+
+impl MetaQuery {
+    /// Defaults to Legend and Diamond, falling back to all ranks if there are not enough
+    /// games, over the past 3 days.
+    #[must_use]
+    pub const fn new(format: Format) -> Self {
+        Self {
+            format,
+            rank: None,
+            period: TimePeriod::Past3Days,
+            min_games: None,
+        }
+    }
+    #[must_use]
+    pub fn with_rank(
+        self,
+        rank: Option<RankBracket>,
+    ) -> Self {
+        Self { rank, ..self }
+    }
+    #[must_use]
+    pub fn with_period(
+        self,
+        period: TimePeriod,
+    ) -> Self {
+        Self { period, ..self }
+    }
+    /// Decks with fewer games are ignored. Defaults to 100 for Standard and Wild, and 50 for
+    /// Twist.
+    #[must_use]
+    pub fn with_min_games(
+        self,
+        min_games: Option<u32>,
+    ) -> Self {
+        Self { min_games, ..self }
+    }
+
+    /// `None` is the default: Legend and Diamond, falling back to all ranks.
+    #[must_use]
+    pub const fn rank(&self) -> Option<RankBracket> {
+        self.rank
+    }
+    #[must_use]
+    pub const fn period(&self) -> TimePeriod {
+        self.period
+    }
+
+    // `stats` is `decks` or `archetypes`.
+    pub(crate) fn url(
+        &self,
+        stats: &str,
+        rank: RankBracket,
+    ) -> Result<String> {
+        let format = match self.format {
+            Format::Standard => "standard",
+            Format::Wild => "wild",
+            Format::Twist => "twist",
+            _ => anyhow::bail!("Meta decks for this format are not available"),
+        };
+
+        Ok(format!(
+            "https://static.zerotoheroes.com/api/constructed/stats/{stats}/{format}/{rank}/{}/overview-from-hourly.gz.json",
+            self.period
+        ))
+    }
+}
+impl From<Format> for MetaQuery {
+    fn from(format: Format) -> Self {
+        Self::new(format)
+    }
+}
+
+// Serialize is for `SnapshotStore`.
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct FirestoneStats {
+pub(crate) struct FirestoneStats {
     // last_updated: String, // Do I really care about this?
-    deck_stats: Vec<DeckStat>,
+    pub deck_stats: Vec<DeckStat>,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct DeckStat {
+pub(crate) struct DeckStat {
     decklist: CompactString,
     // last_update: String, // If I care about it, how can I use it?
     player_class: Class, // Useful for quick filtering
@@ -49,15 +227,70 @@ impl DeckStat {
     }
 }
 
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FirestoneArchetypes {
+    archetype_stats: Vec<ArchetypeStat>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchetypeStat {
+    pub id: u32,
+    pub name: CompactString,
+    pub hero_card_class: Class,
+    pub total_games: u32,
+    #[serde(default)]
+    pub matchup_info: Vec<MatchupInfo>,
+    #[serde(default)]
+    pub cards_data: Vec<CardData>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MatchupInfo {
+    // Missing for matchups against a whole class.
+    pub opponent_archetype_id: Option<u32>,
+    pub total_games: u32,
+    pub wins: u32,
+}
+
+// Counts of games, or of copies over all games.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct CardData {
+    pub card_id: CompactString,
+    pub drawn_before_mulligan: u32,
+    pub kept_in_mulligan: u32,
+    pub in_hand_after_mulligan: u32,
+    pub in_hand_after_mulligan_then_win: u32,
+    pub drawn: u32,
+    pub drawn_then_win: u32,
+    pub played: u32,
+    pub played_then_win: u32,
+}
+
 #[cached::proc_macro::cached(
     time = 86400, // one day.
     result = true,
 )]
-fn get_firestone_data(link: &'static str) -> Result<FirestoneStats> {
+fn get_firestone_archetypes(link: String) -> Result<FirestoneArchetypes> {
+    get_firestone_json(&link)
+}
+
+#[cached::proc_macro::cached(
+    time = 86400, // one day.
+    result = true,
+)]
+pub(crate) fn get_firestone_data(link: String) -> Result<FirestoneStats> {
+    get_firestone_json(&link)
+}
+
+pub(crate) fn get_firestone_json<T: DeserializeOwned>(link: &str) -> Result<T> {
     let mut counter = 5;
     let ret = loop {
         match AGENT.get(link).call() {
-            Ok(mut res) => break res.body_mut().read_json::<FirestoneStats>()?,
+            Ok(mut res) => break res.body_mut().read_json::<T>()?,
             Err(ureq::Error::Io(err))
                 if counter > 0 && err.kind() == std::io::ErrorKind::ConnectionReset =>
             {
@@ -75,20 +308,20 @@ fn get_firestone_data(link: &'static str) -> Result<FirestoneStats> {
 
 pub fn meta_deck(
     class: Option<Class>,
-    format: Format,
+    query: impl Into<MetaQuery>,
     locale: Locale,
 ) -> Result<impl Iterator<Item = Deck>> {
-    let decks =
-        get_decks_stats(format, class)?.filter_map(move |ds| get_deck_from_deck_stat(ds, locale));
+    let decks = get_decks_stats(&query.into(), class)?
+        .filter_map(move |ds| get_deck_from_deck_stat(ds, locale));
 
     Ok(decks)
 }
 
 pub fn meta_snap(
-    format: Format,
+    query: impl Into<MetaQuery>,
     locale: Locale,
 ) -> Result<impl Iterator<Item = Deck>> {
-    let decks = get_decks_stats(format, None)?
+    let decks = get_decks_stats(&query.into(), None)?
         .unique_by(|ds| ds.archetype_name.clone())
         .filter_map(move |ds| get_deck_from_deck_stat(ds, locale));
 
@@ -97,7 +330,7 @@ pub fn meta_snap(
 
 pub fn meta_search(
     search_term: &str,
-    format: Format,
+    query: impl Into<MetaQuery>,
     locale: Locale,
 ) -> Result<Deck> {
     // This function is ridiculous calling parse::<Class>() so often and redundantly.
@@ -106,7 +339,7 @@ pub fn meta_search(
         .rev() // Class name is usually last.
         .find_map(|s| s.parse::<Class>().ok());
 
-    get_decks_stats(format, class)?
+    get_decks_stats(&query.into(), class)?
         .find(|ds| {
             let at = casify_archetype(&ds.archetype_name).to_lowercase();
             at.eq_ignore_ascii_case(search_term.trim())
@@ -118,7 +351,232 @@ pub fn meta_search(
         .ok_or_else(|| anyhow!("No deck found with this name in this format."))
 }
 
-fn casify_archetype(at: &str) -> CompactString {
+/// One row of the archetype table.
+#[derive(Clone, PartialEq)]
+pub struct ArchetypeStats {
+    pub name: CompactString,
+    pub class: Class,
+    pub total_games: u32,
+    /// Share of all games in the query, from 0 to 1.
+    pub popularity: f64,
+    /// Winrate over all the archetype's games.
+    pub winrate: f64,
+    /// Number of distinct decklists.
+    pub lists: usize,
+}
+
+/// Columns of the archetype table. Numbers sort highest first, and names alphabetically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArchetypeSort {
+    #[default]
+    Games,
+    Popularity,
+    Winrate,
+    Class,
+    Lists,
+    Name,
+}
+impl FromStr for ArchetypeSort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "games" => Self::Games,
+            "popularity" | "pop" => Self::Popularity,
+            "winrate" | "wr" => Self::Winrate,
+            "class" => Self::Class,
+            "lists" => Self::Lists,
+            "name" | "archetype" => Self::Name,
+            _ => anyhow::bail!("Not a valid column."),
+        })
+    }
+}
+
+/// Every archetype of the query's format, with all its decklists counted together.
+///
+/// The minimum games apply to the archetype, not to each decklist.
+pub fn meta_archetypes(
+    query: impl Into<MetaQuery>,
+    sort: ArchetypeSort,
+) -> Result<Vec<ArchetypeStats>> {
+    let query = query.into();
+    let min_count = query.min_games.unwrap_or(min_games(&query.format)?.0);
+    let stats = get_ranked_stats(&query, None, min_count)?;
+
+    let mut archetypes = aggregate_archetypes(&stats, min_count);
+    match sort {
+        ArchetypeSort::Games | ArchetypeSort::Popularity => {}
+        ArchetypeSort::Winrate => {
+            archetypes.sort_by(|a1, a2| a2.winrate.total_cmp(&a1.winrate));
+        }
+        ArchetypeSort::Class => archetypes.sort_by_key(|a| a.class as u8),
+        ArchetypeSort::Lists => archetypes.sort_by_key(|a| std::cmp::Reverse(a.lists)),
+        ArchetypeSort::Name => archetypes.sort_by(|a1, a2| a1.name.cmp(&a2.name)),
+    }
+
+    Ok(archetypes)
+}
+
+// Winrates that start each tier, best first. Lower winrates are in the last tier.
+const TIER_WINRATES: [f64; 4] = [0.55, 0.52, 0.50, 0.47];
+
+/// Archetypes bucketed into tiers by winrate.
+pub struct TierList {
+    pub format: Format,
+    /// Best tier first, and best archetype first in each. Empty tiers are kept, so the first
+    /// one is always Tier 1.
+    pub tiers: Vec<Vec<ArchetypeStats>>,
+}
+impl TierList {
+    #[must_use]
+    pub fn get_image(&self) -> image::RgbaImage {
+        crate::deck_image::tier_list(self)
+    }
+}
+
+/// Every archetype of `meta_archetypes` in a tier. Tier 1 starts at 55% winrate, then every
+/// tier starts a few points lower.
+///
+/// Tiers use the archetype stats, so an archetype's winrate is over all its lists, unlike
+/// `meta_snap`, which only picks the best performing list of each archetype.
+pub fn tier_list(query: impl Into<MetaQuery>) -> Result<TierList> {
+    let query = query.into();
+    let format = query.format.clone();
+
+    let mut tiers = vec![Vec::new(); TIER_WINRATES.len() + 1];
+    for archetype in meta_archetypes(query, ArchetypeSort::Winrate)? {
+        tiers[tier_of(archetype.winrate)].push(archetype);
+    }
+
+    Ok(TierList { format, tiers })
+}
+
+fn tier_of(winrate: f64) -> usize {
+    TIER_WINRATES
+        .iter()
+        .position(|&w| winrate >= w)
+        .unwrap_or(TIER_WINRATES.len())
+}
+
+/// Archetypes of the deck's class and format, closest first, scored from 0 to 1.
+///
+/// The score is the weighted card overlap with the archetype's closest decklist: copies
+/// shared by both lists over copies in either list.
+pub fn classify(deck: &Deck) -> Result<Vec<(CompactString, f64)>> {
+    let ids = deck.canonical_ids();
+
+    let scores = get_decks_stats(&deck.format.clone().into(), Some(deck.class))?
+        .filter_map(|ds| {
+            let list = canonical_ids(&ds.decklist).ok()?;
+            Some((ds.archetype_name, weighted_overlap(&ids, &list)))
+        })
+        .into_grouping_map()
+        .max_by(|_, s1, s2| s1.total_cmp(s2))
+        .into_iter()
+        .map(|(archetype, score)| (casify_archetype(&archetype), score))
+        .sorted_by(|(_, s1), (_, s2)| s2.total_cmp(s1))
+        .collect();
+
+    Ok(scores)
+}
+
+/// A meta deck close to a given deck. See [`similar_decks`].
+pub struct SimilarDeck {
+    /// Titled like `meta_deck`'s decks.
+    pub deck: Deck,
+    /// From 0 to 1. See [`classify`].
+    pub similarity: f64,
+    pub winrate: f64,
+    pub total_games: u32,
+    /// `deck1` is the given deck and `deck2` is this one. `deck1_uniques` are the cards to
+    /// take out, and `deck2_uniques` the cards to put in.
+    pub swaps: DeckDifference,
+}
+impl Localize for SimilarDeck {
+    fn in_locale(
+        &self,
+        locale: Locale,
+    ) -> impl Display {
+        let mut f = String::new();
+
+        writeln!(
+            f,
+            "{:.0}% similar: {}",
+            self.similarity * 100.0,
+            self.deck.title.bold()
+        )
+        .ok();
+        writeln!(f, "{}", self.deck.deck_code).ok();
+
+        for (card, count) in &self.swaps.deck1_uniques.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(f, "{}{count:>3} {}", "-".red(), card.in_locale(locale)).ok();
+        }
+        for (card, count) in &self.swaps.deck2_uniques.iter().collect::<BTreeMap<_, _>>() {
+            writeln!(f, "{}{count:>3} {}", "+".green(), card.in_locale(locale)).ok();
+        }
+
+        f
+    }
+}
+
+/// The meta decks of the deck's class and format closest to it, closest first.
+///
+/// Only the `count` closest decklists are looked up.
+pub fn similar_decks(
+    deck: &Deck,
+    count: usize,
+    locale: Locale,
+) -> Result<Vec<SimilarDeck>> {
+    let ids = deck.canonical_ids();
+
+    let similar = get_decks_stats(&deck.format.clone().into(), Some(deck.class))?
+        .filter_map(|ds| {
+            let list = canonical_ids(&ds.decklist).ok()?;
+            Some((weighted_overlap(&ids, &list), ds))
+        })
+        .sorted_by(|(s1, _), (s2, _)| s2.total_cmp(s1))
+        .take(count)
+        .filter_map(|(similarity, ds)| {
+            let (winrate, total_games) = (ds.get_winrate(), ds.total_games);
+            let meta_deck = get_deck_from_deck_stat(ds, locale)?;
+            Some(SimilarDeck {
+                swaps: deck.compare_with(&meta_deck),
+                deck: meta_deck,
+                similarity,
+                winrate,
+                total_games,
+            })
+        })
+        .collect();
+
+    Ok(similar)
+}
+
+// Weighted Jaccard similarity of two sorted lists of card IDs.
+#[allow(clippy::cast_precision_loss)]
+fn weighted_overlap(
+    list1: &[usize],
+    list2: &[usize],
+) -> f64 {
+    use itertools::EitherOrBoth::{Both, Left, Right};
+
+    let (shared, total) = list1
+        .iter()
+        .dedup_with_count()
+        .merge_join_by(list2.iter().dedup_with_count(), |(_, a), (_, b)| a.cmp(b))
+        .fold((0, 0), |(shared, total), pair| match pair {
+            Both((c1, _), (c2, _)) => (shared + c1.min(c2), total + c1.max(c2)),
+            Left((c, _)) | Right((c, _)) => (shared, total + c),
+        });
+
+    if total == 0 {
+        0.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+pub(crate) fn casify_archetype(at: &str) -> CompactString {
     at.split('-')
         .map(|s| {
             if s.eq_ignore_ascii_case("dk") // Death Knight
@@ -166,35 +624,212 @@ fn get_deck_from_deck_stat(
     Some(deck)
 }
 
+/// Whether Firestone publishes meta stats for the format.
+#[must_use]
+pub fn has_meta(format: &Format) -> bool {
+    matches!(format, Format::Standard | Format::Wild | Format::Twist)
+}
+
+// Minimum games, and the log2 of the games above which decks are sorted by winrate alone.
+pub(crate) fn min_games(format: &Format) -> Result<(u32, u32)> {
+    Ok(match format {
+        Format::Standard => (100, 10), // 2^10 == 1024
+        Format::Wild => (100, 9),      // 2^9  == 512
+        Format::Twist => (50, 7),      // 2^7  == 128
+        _ => anyhow::bail!("Meta decks for this format are not available"),
+    })
+}
+
 fn get_decks_stats(
-    format: Format,
+    query: &MetaQuery,
     class: Option<Class>,
-) -> Result<impl Iterator<Item = DeckStat>> {
-    let (d_l, all, min_count, min_log) = match format {
-        Format::Standard => (STANDARD_DECKS_D_L, STANDARD_DECKS_ALL, 100, 10), // 2^10 == 1024
-        Format::Wild => (WILD_DECKS_D_L, WILD_DECKS_ALL, 100, 9),              // 2^9  == 512
-        Format::Twist => (TWIST_DECKS_D_L, TWIST_DECKS_ALL, 50, 7),            // 2^7  == 128
-        _ => anyhow::bail!("Meta decks for this format are not available"),
-    };
+) -> Result<impl Iterator<Item = DeckStat> + use<>> {
+    let (min_count, min_log) = min_games(&query.format)?;
+    let min_count = query.min_games.unwrap_or(min_count);
 
-    let mut range = d_l;
-    loop {
-        let mut decks = get_firestone_data(range)?
-            .deck_stats
+    let decks = get_ranked_stats(query, class, min_count)?
+        .into_iter()
+        .filter(move |s| s.total_games >= min_count && class.is_none_or(|c| c == s.player_class))
+        .sorted_by(|s1, s2| {
+            (s2.total_games.ilog2().min(min_log))
+                .cmp(&s1.total_games.ilog2().min(min_log))
+                .then(s2.get_winrate().total_cmp(&s1.get_winrate()))
+        });
+
+    Ok(decks)
+}
+
+// All decks of the query's bracket. Without a chosen bracket, falls back to all ranks when the
+// top ones have no deck of the class with enough games.
+fn get_ranked_stats(
+    query: &MetaQuery,
+    class: Option<Class>,
+    min_count: u32,
+) -> Result<Vec<DeckStat>> {
+    with_rank_fallback(query, min_count, |rank| {
+        let stats = get_firestone_data(query.url("decks", rank)?)?.deck_stats;
+        let usable = stats
+            .iter()
+            .any(|s| s.total_games >= min_count && class.is_none_or(|c| c == s.player_class));
+        Ok(usable.then_some(stats))
+    })
+}
+
+/// Archetypes of the query's bracket with enough games, most played first.
+pub(crate) fn get_archetype_stats(query: &MetaQuery) -> Result<Vec<ArchetypeStat>> {
+    let min_count = query.min_games.unwrap_or(min_games(&query.format)?.0);
+
+    with_rank_fallback(query, min_count, |rank| {
+        let stats = get_firestone_archetypes(query.url("archetypes", rank)?)?
+            .archetype_stats
             .into_iter()
-            .filter(|s| s.total_games >= min_count && class.is_none_or(|c| c == s.player_class))
-            .peekable();
-
-        match decks.peek() {
-            None if range == d_l => range = all,
-            None => anyhow::bail!("No decks found with more than {min_count} games."),
-            _ => {
-                return Ok(decks.sorted_by(|s1, s2| {
-                    (s2.total_games.ilog2().min(min_log))
-                        .cmp(&s1.total_games.ilog2().min(min_log))
-                        .then(s2.get_winrate().total_cmp(&s1.get_winrate()))
-                }));
-            }
+            .filter(|s| s.total_games >= min_count)
+            .sorted_by_key(|s| std::cmp::Reverse(s.total_games))
+            .collect_vec();
+        Ok(stats.is_empty().not().then_some(stats))
+    })
+}
+
+/// The archetype named `name`, ignoring case. Part of the name is enough.
+pub(crate) fn find_archetype<'s>(
+    stats: &'s [ArchetypeStat],
+    name: &str,
+) -> Result<&'s ArchetypeStat> {
+    let name = name.trim().to_lowercase().replace(' ', "-");
+
+    stats
+        .iter()
+        .find(|s| s.name.eq_ignore_ascii_case(&name))
+        .or_else(|| stats.iter().find(|s| s.name.to_lowercase().contains(&name)))
+        .ok_or_else(|| anyhow!("No archetype found with this name in this format."))
+}
+
+// `fetch` gives `None` when the bracket has too few games. Without a chosen bracket, Legend and
+// Diamond are tried first, then all ranks.
+fn with_rank_fallback<T>(
+    query: &MetaQuery,
+    min_count: u32,
+    mut fetch: impl FnMut(RankBracket) -> Result<Option<T>>,
+) -> Result<T> {
+    let mut rank = query.rank.unwrap_or(RankBracket::LegendDiamond);
+    loop {
+        if let Some(ret) = fetch(rank)? {
+            return Ok(ret);
         }
+
+        if query.rank.is_none() && rank != RankBracket::All {
+            rank = RankBracket::All;
+        } else {
+            anyhow::bail!("No decks found with more than {min_count} games.");
+        }
+    }
+}
+
+// Sorted by games, most first.
+pub(crate) fn aggregate_archetypes(
+    stats: &[DeckStat],
+    min_count: u32,
+) -> Vec<ArchetypeStats> {
+    let all_games = stats.iter().map(|s| u64::from(s.total_games)).sum::<u64>();
+
+    stats
+        .iter()
+        .into_group_map_by(|s| &s.archetype_name)
+        .into_iter()
+        .filter_map(|(name, lists)| {
+            let total_games = lists.iter().map(|s| s.total_games).sum::<u32>();
+            let total_wins = lists.iter().map(|s| s.total_wins).sum::<u32>();
+            (total_games >= min_count.max(1)).then(|| ArchetypeStats {
+                name: casify_archetype(name),
+                class: lists[0].player_class,
+                total_games,
+                #[allow(clippy::cast_precision_loss)]
+                popularity: f64::from(total_games) / all_games as f64,
+                winrate: f64::from(total_wins) / f64::from(total_games),
+                lists: lists.iter().map(|s| &s.decklist).unique().count(),
+            })
+        })
+        .sorted_by(|a1, a2| {
+            a2.total_games
+                .cmp(&a1.total_games)
+                .then(a1.name.cmp(&a2.name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlap() {
+        assert!((weighted_overlap(&[1, 1, 2, 3], &[1, 1, 2, 3]) - 1.0).abs() < f64::EPSILON);
+        assert!((weighted_overlap(&[1, 1, 2], &[1, 3, 3]) - 0.2).abs() < f64::EPSILON);
+        assert!(weighted_overlap(&[1, 2], &[3, 4]).abs() < f64::EPSILON);
+        assert!(weighted_overlap(&[], &[]).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn query_url() {
+        let query = MetaQuery::new(Format::Wild).with_period("patch".parse().unwrap());
+        assert_eq!(
+            query.url("decks", RankBracket::Legend).unwrap(),
+            "https://static.zerotoheroes.com/api/constructed/stats/decks/wild/legend/last-patch/overview-from-hourly.gz.json"
+        );
+        assert!(
+            MetaQuery::new(Format::Classic)
+                .url("decks", RankBracket::All)
+                .is_err()
+        );
+        for format in [
+            Format::Standard,
+            Format::Wild,
+            Format::Twist,
+            Format::Classic,
+        ] {
+            let url = MetaQuery::new(format.clone()).url("decks", RankBracket::All);
+            assert_eq!(has_meta(&format), url.is_ok());
+        }
+        assert_eq!(
+            "Bronze Gold".parse::<RankBracket>().unwrap(),
+            RankBracket::BronzeGold
+        );
+    }
+
+    #[test]
+    fn archetype_table() {
+        let stat = |decklist: &str, archetype: &str, total_games, total_wins| DeckStat {
+            decklist: decklist.into(),
+            player_class: Class::Mage,
+            total_games,
+            total_wins,
+            winrate: None,
+            archetype_name: archetype.into(),
+        };
+        let stats = [
+            stat("a", "big-spell-mage", 300, 150),
+            stat("b", "big-spell-mage", 100, 70),
+            stat("c", "arcane-mage", 500, 200),
+            stat("d", "tiny-mage", 100, 100),
+        ];
+
+        let table = aggregate_archetypes(&stats, 200);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[0].name, "Arcane Mage");
+        assert_eq!(table[1].name, "Big Spell Mage");
+        assert_eq!(table[1].total_games, 400);
+        assert_eq!(table[1].lists, 2);
+        assert!((table[1].popularity - 0.4).abs() < f64::EPSILON);
+        assert!((table[1].winrate - 0.55).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn tiers() {
+        assert_eq!(tier_of(0.6), 0);
+        assert_eq!(tier_of(0.55), 0);
+        assert_eq!(tier_of(0.53), 1);
+        assert_eq!(tier_of(0.5), 2);
+        assert_eq!(tier_of(0.48), 3);
+        assert_eq!(tier_of(0.3), 4);
     }
 }
@@ -0,0 +1,108 @@
+use crate::{
+    hearth_sim::{get_hearth_sim_dbf_ids, validate_id},
+    localization::{Locale, Localize},
+    meta::{CardData, MetaQuery, find_archetype, get_archetype_stats},
+};
+use anyhow::Result;
+use std::{collections::HashMap, fmt::Display};
+
+/// How a card does in an archetype. Each rate is `None` when there are no games to count.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CardStats {
+    /// Winrate of games where the card is in hand after the mulligan.
+    pub mulligan_winrate: Option<f64>,
+    /// How often the card is kept when it is in the opening hand.
+    pub kept: Option<f64>,
+    /// Winrate of games where the card is drawn.
+    pub drawn_winrate: Option<f64>,
+    /// Winrate of games where the card is played.
+    pub played_winrate: Option<f64>,
+}
+
+impl From<&CardData> for CardStats {
+    fn from(data: &CardData) -> Self {
+        let rate = |part: u32, total: u32| (total > 0).then(|| f64::from(part) / f64::from(total));
+
+        Self {
+            mulligan_winrate: rate(
+                data.in_hand_after_mulligan_then_win,
+                data.in_hand_after_mulligan,
+            ),
+            kept: rate(data.kept_in_mulligan, data.drawn_before_mulligan),
+            drawn_winrate: rate(data.drawn_then_win, data.drawn),
+            played_winrate: rate(data.played_then_win, data.played),
+        }
+    }
+}
+
+impl Localize for CardStats {
+    fn in_locale(
+        &self,
+        _: Locale,
+    ) -> impl Display {
+        format!(
+            "mulligan {} (kept {}), drawn {}, played {}",
+            percent(self.mulligan_winrate),
+            percent(self.kept),
+            percent(self.drawn_winrate),
+            percent(self.played_winrate)
+        )
+    }
+}
+
+pub(crate) fn percent(rate: Option<f64>) -> String {
+    rate.map_or_else(|| String::from("-"), |r| format!("{:.1}%", r * 100.0))
+}
+
+/// Stats of the cards played in an archetype of the query's format, by card id. Names are
+/// matched like `matchup`.
+///
+/// Give them to `Deck::with_card_stats` to show them with the deck.
+pub fn card_stats(
+    archetype: &str,
+    query: impl Into<MetaQuery>,
+) -> Result<HashMap<usize, CardStats>> {
+    let stats = get_archetype_stats(&query.into())?;
+    let archetype = find_archetype(&stats, archetype)?;
+
+    let ids = get_hearth_sim_dbf_ids(archetype.cards_data.iter().map(|d| d.card_id.as_str()));
+
+    Ok(archetype
+        .cards_data
+        .iter()
+        .filter_map(|data| {
+            let id = validate_id(*ids.get(data.card_id.as_str())?);
+            Some((id, CardStats::from(data)))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card_rates() {
+        let data = CardData {
+            drawn_before_mulligan: 200,
+            kept_in_mulligan: 150,
+            in_hand_after_mulligan: 160,
+            in_hand_after_mulligan_then_win: 96,
+            drawn: 500,
+            drawn_then_win: 260,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            CardStats::from(&data),
+            CardStats {
+                mulligan_winrate: Some(0.6),
+                kept: Some(0.75),
+                drawn_winrate: Some(0.52),
+                played_winrate: None,
+            }
+        );
+        assert_eq!(percent(Some(0.525)), "52.5%");
+        assert_eq!(percent(None), "-");
+    }
+}
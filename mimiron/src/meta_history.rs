@@ -0,0 +1,322 @@
+use crate::{
+    card_details::Class,
+    meta::{
+        ArchetypeStats, FirestoneStats, MetaQuery, RankBracket, aggregate_archetypes,
+        get_firestone_data, get_firestone_json, min_games,
+    },
+};
+use anyhow::{Result, anyhow};
+use compact_str::CompactString;
+use itertools::Itertools;
+use jiff::{ToSpan, civil::Date};
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
+
+/// Daily copies of Firestone's deck stats, kept in a local directory. One file per day, format,
+/// rank bracket, and time period.
+///
+/// Without a chosen bracket, Legend and Diamond are saved, without falling back to all ranks,
+/// so that snapshots stay comparable.
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+/// The snapshot to compare the latest one with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Since {
+    /// This many days before today. 1 is yesterday.
+    Days(i16),
+    Date(Date),
+    /// The day of the latest balance patch, according to Firestone.
+    LastPatch,
+}
+impl FromStr for Since {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "yesterday" => Self::Days(1),
+            "week" | "last-week" => Self::Days(7),
+            "patch" | "last-patch" => Self::LastPatch,
+            s => match s.parse::<i16>() {
+                Ok(days) => Self::Days(days),
+                Err(_) => Self::Date(s.parse().map_err(|_| {
+                    anyhow!("Expected yesterday, last-patch, a number of days, or a date.")
+                })?),
+            },
+        })
+    }
+}
+
+/// An archetype on one day.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrendPoint {
+    pub date: Date,
+    pub total_games: u32,
+    pub popularity: f64,
+    pub winrate: f64,
+}
+
+/// How an archetype changed between two snapshots. Changes are in points, from -1 to 1.
+#[derive(Clone, PartialEq)]
+pub struct Mover {
+    pub name: CompactString,
+    pub class: Class,
+    pub winrate: f64,
+    pub winrate_change: f64,
+    pub popularity: f64,
+    pub popularity_change: f64,
+}
+
+pub struct MetaChanges {
+    pub from: Date,
+    pub to: Date,
+    /// Biggest winrate risers first, and biggest fallers last.
+    pub movers: Vec<Mover>,
+}
+
+impl SnapshotStore {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Fetches the query's stats and saves them as today's snapshot, replacing any saved earlier
+    /// today.
+    pub fn save(
+        &self,
+        query: &MetaQuery,
+    ) -> Result<Date> {
+        let today = jiff::Zoned::now().date();
+        let stats = get_firestone_data(query.url("decks", rank(query))?)?;
+
+        let path = self.path(query, today);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(&stats)?)?;
+
+        Ok(today)
+    }
+
+    /// Days with a saved snapshot for the query, oldest first.
+    pub fn dates(
+        &self,
+        query: &MetaQuery,
+    ) -> Result<Vec<Date>> {
+        let Ok(entries) = std::fs::read_dir(self.dir.join(key(query))) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(entries
+            .filter_map(|e| {
+                let path = e.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse::<Date>().ok()
+            })
+            .sorted()
+            .collect())
+    }
+
+    /// Winrate and popularity of an archetype on every saved day it was played. Names are
+    /// matched like `matchup`, against the latest snapshot.
+    pub fn archetype_trend(
+        &self,
+        archetype: &str,
+        query: &MetaQuery,
+    ) -> Result<Vec<TrendPoint>> {
+        let dates = self.dates(query)?;
+        let latest = dates
+            .last()
+            .ok_or_else(|| anyhow!("No saved snapshots for this query."))?;
+
+        let search = slug(archetype.trim());
+        let latest = self.archetypes(query, *latest, 0)?;
+        let name = latest
+            .iter()
+            .find(|a| slug(&a.name) == search)
+            .or_else(|| latest.iter().find(|a| slug(&a.name).contains(&search)))
+            .map(|a| a.name.clone())
+            .ok_or_else(|| anyhow!("No archetype found with this name in this format."))?;
+
+        dates
+            .into_iter()
+            .filter_map(|date| {
+                let archetypes = match self.archetypes(query, date, 0) {
+                    Ok(archetypes) => archetypes,
+                    Err(e) => return Some(Err(e)),
+                };
+                let a = archetypes.into_iter().find(|a| a.name == name)?;
+                Some(Ok(TrendPoint {
+                    date,
+                    total_games: a.total_games,
+                    popularity: a.popularity,
+                    winrate: a.winrate,
+                }))
+            })
+            .collect()
+    }
+
+    /// Changes of every archetype between the latest snapshot and the last one on or before
+    /// `since`. Archetypes need the query's minimum games in both snapshots.
+    pub fn movers(
+        &self,
+        query: &MetaQuery,
+        since: Since,
+    ) -> Result<MetaChanges> {
+        let since = match since {
+            Since::Days(days) => jiff::Zoned::now().date().checked_sub(days.days())?,
+            Since::Date(date) => date,
+            Since::LastPatch => last_patch_date()?,
+        };
+
+        let dates = self.dates(query)?;
+        let to = *dates
+            .last()
+            .ok_or_else(|| anyhow!("No saved snapshots for this query."))?;
+        let from = *dates
+            .iter()
+            .rev()
+            .find(|&&d| d <= since && d < to)
+            .ok_or_else(|| anyhow!("No snapshot saved on {since} or before."))?;
+
+        let min_count = query.min_games.unwrap_or(min_games(&query.format)?.0);
+
+        Ok(MetaChanges {
+            from,
+            to,
+            movers: changes(
+                &self.archetypes(query, from, min_count)?,
+                &self.archetypes(query, to, min_count)?,
+            ),
+        })
+    }
+
+    fn archetypes(
+        &self,
+        query: &MetaQuery,
+        date: Date,
+        min_count: u32,
+    ) -> Result<Vec<ArchetypeStats>> {
+        let stats =
+            serde_json::from_slice::<FirestoneStats>(&std::fs::read(self.path(query, date))?)?;
+        Ok(aggregate_archetypes(&stats.deck_stats, min_count))
+    }
+
+    fn path(
+        &self,
+        query: &MetaQuery,
+        date: Date,
+    ) -> PathBuf {
+        self.dir.join(key(query)).join(format!("{date}.json"))
+    }
+}
+
+fn rank(query: &MetaQuery) -> RankBracket {
+    query.rank.unwrap_or(RankBracket::LegendDiamond)
+}
+
+fn key(query: &MetaQuery) -> String {
+    format!("{}-{}-{}", query.format, rank(query), query.period).to_lowercase()
+}
+
+// Archetype names as Firestone writes them, like "big-spell-mage".
+fn slug(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
+}
+
+fn changes(
+    before: &[ArchetypeStats],
+    after: &[ArchetypeStats],
+) -> Vec<Mover> {
+    let before = before
+        .iter()
+        .map(|a| (&a.name, a))
+        .collect::<HashMap<_, _>>();
+
+    after
+        .iter()
+        .filter_map(|a| {
+            let b = before.get(&a.name)?;
+            Some(Mover {
+                name: a.name.clone(),
+                class: a.class,
+                winrate: a.winrate,
+                winrate_change: a.winrate - b.winrate,
+                popularity: a.popularity,
+                popularity_change: a.popularity - b.popularity,
+            })
+        })
+        .sorted_by(|m1, m2| m2.winrate_change.total_cmp(&m1.winrate_change))
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PatchesConfig {
+    current_constructed_meta_patch: u32,
+    patches: Vec<PatchInfo>,
+}
+
+#[derive(Deserialize)]
+struct PatchInfo {
+    number: u32,
+    // ISO 8601, like "2024-03-19T17:10:00.000Z".
+    date: CompactString,
+}
+
+fn last_patch_date() -> Result<Date> {
+    let config = get_firestone_json::<PatchesConfig>(
+        "https://static.zerotoheroes.com/hearthstone/data/patches.json",
+    )?;
+
+    config
+        .patches
+        .iter()
+        .find(|p| p.number == config.current_constructed_meta_patch)
+        .and_then(|p| p.date.get(..10)?.parse().ok())
+        .ok_or_else(|| anyhow!("Could not find the date of the last patch."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn risers_and_fallers() {
+        let stats = |name: &str, popularity, winrate| ArchetypeStats {
+            name: name.into(),
+            class: Class::Mage,
+            total_games: 1000,
+            popularity,
+            winrate,
+            lists: 1,
+        };
+        let before = [
+            stats("Big Spell Mage", 0.1, 0.5),
+            stats("Arcane Mage", 0.2, 0.55),
+            stats("Gone Mage", 0.05, 0.5),
+        ];
+        let after = [
+            stats("Arcane Mage", 0.1, 0.5),
+            stats("Big Spell Mage", 0.25, 0.53),
+            stats("New Mage", 0.05, 0.6),
+        ];
+
+        let movers = changes(&before, &after);
+        assert_eq!(movers.len(), 2);
+        assert_eq!(movers[0].name, "Big Spell Mage");
+        assert!((movers[0].popularity_change - 0.15).abs() < 1e-9);
+        assert_eq!(movers[1].name, "Arcane Mage");
+        assert!((movers[1].winrate_change + 0.05).abs() < 1e-9);
+
+        assert_eq!("yesterday".parse::<Since>().unwrap(), Since::Days(1));
+        assert_eq!(
+            "2026-10-01".parse::<Since>().unwrap(),
+            Since::Date(jiff::civil::date(2026, 10, 1))
+        );
+        assert!("tomorrow".parse::<Since>().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    card_details::Class,
+    localization::{Locale, Localize},
+    meta::{ArchetypeStat, MetaQuery, casify_archetype, find_archetype, get_archetype_stats},
+};
+use anyhow::{Result, anyhow};
+use colored::Colorize;
+use compact_str::CompactString;
+use itertools::Itertools;
+use std::fmt::{Display, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matchup {
+    pub winrate: f64,
+    pub total_games: u32,
+}
+
+/// Matchups between the most played archetypes of a format.
+pub struct MatchupMatrix {
+    /// Most played first.
+    pub archetypes: Vec<(CompactString, Class)>,
+    // `cells[a * len + b]` is `a` against `b`.
+    cells: Vec<Option<Matchup>>,
+}
+
+impl MatchupMatrix {
+    /// How the archetype at `archetype` fares against the one at `opponent`. `None` when they
+    /// have not played each other.
+    #[must_use]
+    pub fn get(
+        &self,
+        archetype: usize,
+        opponent: usize,
+    ) -> Option<Matchup> {
+        self.cells
+            .get(archetype * self.archetypes.len() + opponent)
+            .copied()
+            .flatten()
+    }
+
+    #[must_use]
+    pub fn get_image(&self) -> image::RgbaImage {
+        crate::deck_image::matchups(self)
+    }
+}
+
+impl Localize for MatchupMatrix {
+    fn in_locale(
+        &self,
+        _: Locale,
+    ) -> impl Display {
+        let mut buffer = String::new();
+        let name_width = self
+            .archetypes
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        write!(buffer, "{:name_width$}    ", "").ok();
+        for i in 1..=self.archetypes.len() {
+            write!(buffer, "{i:>6}").ok();
+        }
+
+        for (i, (name, _)) in self.archetypes.iter().enumerate() {
+            write!(buffer, "\n{:>2}. {name:<name_width$}", i + 1).ok();
+            for j in 0..self.archetypes.len() {
+                let cell = match self.get(i, j) {
+                    Some(m) => {
+                        let (r, g, b) = heat(m.winrate);
+                        format!(" {:>4.1} ", m.winrate * 100.0)
+                            .black()
+                            .on_truecolor(r, g, b)
+                    }
+                    None => format!(" {:>4} ", "-").normal(),
+                };
+                write!(buffer, "{cell}").ok();
+            }
+        }
+
+        write!(
+            buffer,
+            "\n\nWinrate of each row's archetype against each column's."
+        )
+        .ok();
+
+        buffer
+    }
+}
+
+/// How `archetype` fares against `opponent` in the query's format. Names are matched ignoring
+/// case, and part of the name is enough.
+pub fn matchup(
+    archetype: &str,
+    opponent: &str,
+    query: impl Into<MetaQuery>,
+) -> Result<Matchup> {
+    let stats = get_archetype_stats(&query.into())?;
+    let archetype = find_archetype(&stats, archetype)?;
+    let opponent = find_archetype(&stats, opponent)?;
+
+    matchup_against(archetype, opponent.id).ok_or_else(|| {
+        anyhow!(
+            "No games between {} and {}.",
+            casify_archetype(&archetype.name),
+            casify_archetype(&opponent.name)
+        )
+    })
+}
+
+/// Matchups between the `size` most played archetypes of the query's format.
+pub fn matchup_matrix(
+    query: impl Into<MetaQuery>,
+    size: usize,
+) -> Result<MatchupMatrix> {
+    let stats = get_archetype_stats(&query.into())?;
+    let stats = &stats[..size.min(stats.len())];
+
+    Ok(MatchupMatrix {
+        archetypes: stats
+            .iter()
+            .map(|s| (casify_archetype(&s.name), s.hero_card_class))
+            .collect(),
+        cells: stats
+            .iter()
+            .cartesian_product(stats)
+            .map(|(a, b)| matchup_against(a, b.id))
+            .collect(),
+    })
+}
+
+/// Red below 50% winrate, and green above, fully saturated at 40% and 60%.
+pub(crate) fn heat(winrate: f64) -> (u8, u8, u8) {
+    let t = ((winrate - 0.5) * 10.0).clamp(-1.0, 1.0);
+    let (r, g, b) = if t < 0.0 {
+        (230.0, 80.0, 80.0)
+    } else {
+        (80.0, 190.0, 100.0)
+    };
+    let t = t.abs();
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let mix = |c: f64| (255.0 + (c - 255.0) * t).round() as u8;
+    (mix(r), mix(g), mix(b))
+}
+
+fn matchup_against(
+    archetype: &ArchetypeStat,
+    opponent_id: u32,
+) -> Option<Matchup> {
+    let (total_games, wins) = archetype
+        .matchup_info
+        .iter()
+        .filter(|m| m.opponent_archetype_id == Some(opponent_id))
+        .fold((0, 0), |(games, wins), m| {
+            (games + m.total_games, wins + m.wins)
+        });
+
+    (total_games > 0).then(|| Matchup {
+        winrate: f64::from(wins) / f64::from(total_games),
+        total_games,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::MatchupInfo;
+
+    #[test]
+    fn matchups() {
+        let info = |opponent_archetype_id, total_games, wins| MatchupInfo {
+            opponent_archetype_id,
+            total_games,
+            wins,
+        };
+        let stat = ArchetypeStat {
+            id: 1,
+            name: "big-spell-mage".into(),
+            hero_card_class: Class::Mage,
+            total_games: 1000,
+            matchup_info: vec![
+                info(Some(2), 300, 120),
+                info(None, 500, 400),
+                info(Some(3), 0, 0),
+            ],
+            cards_data: Vec::new(),
+        };
+
+        assert_eq!(
+            matchup_against(&stat, 2),
+            Some(Matchup {
+                winrate: 0.4,
+                total_games: 300
+            })
+        );
+        assert_eq!(matchup_against(&stat, 3), None);
+        assert_eq!(matchup_against(&stat, 4), None);
+
+        assert_eq!(heat(0.5), (255, 255, 255));
+        assert_eq!(heat(0.3), (230, 80, 80));
+        assert_eq!(heat(0.65), (80, 190, 100));
+    }
+}
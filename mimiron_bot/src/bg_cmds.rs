@@ -2,11 +2,13 @@ use crate::{
     Context, Error,
     helpers::{Emoji, get_server_locale, paginated_embeds},
 };
+use itertools::Itertools;
 use mimiron::{
     CardTextDisplay, bg,
     localization::{Locale, Localize},
 };
 use poise::serenity_prelude as serenity;
+use std::ops::Not;
 
 /// alias for /bg
 #[poise::command(slash_command, install_context = "Guild|User", hide_in_help)]
@@ -158,6 +160,37 @@ fn inner_card_embed(
         bg::BGCardType::HeroPower { text, .. } => (text.to_markdown(), vec![]),
     };
 
+    // Firestone's hero stats and top compositions, for each mode.
+    if matches!(card.card_type, bg::BGCardType::Hero { .. }) {
+        for pool in [bg::Pool::Solos, bg::Pool::Duos] {
+            let stats = bg::hero_stats(pool)
+                .inspect_err(|e| tracing::warn!("Could not get hero stats: {e}"))
+                .ok();
+            if let Some(stats) = stats.as_ref().and_then(|s| s.get(&card.id)) {
+                fields.push((
+                    format!("{}Stats", pool.emoji()).into(),
+                    stats.in_locale(locale).to_string(),
+                    false,
+                ));
+            }
+
+            let comps = bg::comp_stats(pool)
+                .inspect_err(|e| tracing::warn!("Could not get composition stats: {e}"))
+                .unwrap_or_default();
+            if comps.is_empty().not() {
+                fields.push((
+                    format!("{}Top Comps", pool.emoji()).into(),
+                    comps
+                        .iter()
+                        .take(3)
+                        .map(|c| c.in_locale(locale).to_string())
+                        .join("\n"),
+                    false,
+                ));
+            }
+        }
+    }
+
     // Buddies, Golden Minions, and Hero Powers.
     fields.extend(bg::get_associated_cards(card, locale, false).filter_map(
         |(assoc_card, assoc)| {
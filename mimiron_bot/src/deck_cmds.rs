@@ -1,17 +1,20 @@
 use crate::{
     Context, Error,
-    helpers::{Emoji, get_server_locale},
+    helpers::{Emoji, get_server_locale, paginated_embeds},
 };
 use itertools::Itertools;
 use mimiron::{
     card,
-    deck::{self, Deck, LookupOptions},
-    localization::Localize,
+    deck::{
+        self, Deck, LookupOptions,
+        odds::{Mulligan, Odds},
+    },
+    localization::{Locale, Localize},
     meta,
 };
 use poise::serenity_prelude as serenity;
 use rand::random;
-use std::{cell::LazyCell, collections::HashMap, io::Cursor};
+use std::{cell::LazyCell, collections::HashMap, fmt::Write, io::Cursor, ops::Not};
 
 /// Get deck image from code
 #[poise::command(slash_command, install_context = "Guild|User", category = "Deck")]
@@ -25,10 +28,19 @@ pub async fn deck(
     #[description = "mode"]
     #[autocomplete = "autocomplete_shape"]
     shape: Option<String>,
+    #[description = "name after archetype if untitled"] archetype: Option<bool>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
-    deck_inner(ctx, code, title, format, shape).await
+    deck_inner(
+        ctx,
+        code,
+        title,
+        format,
+        shape,
+        archetype.unwrap_or_default(),
+    )
+    .await
 }
 
 /// alias for deck
@@ -43,10 +55,19 @@ pub async fn code(
     #[description = "mode"]
     #[autocomplete = "autocomplete_shape"]
     shape: Option<String>,
+    #[description = "name after archetype if untitled"] archetype: Option<bool>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
-    deck_inner(ctx, code, title, format, shape).await
+    deck_inner(
+        ctx,
+        code,
+        title,
+        format,
+        shape,
+        archetype.unwrap_or_default(),
+    )
+    .await
 }
 
 #[allow(clippy::unused_async)]
@@ -64,7 +85,7 @@ async fn autocomplete_shape<'a>(
     _: Context<'_>,
     partial: &'a str,
 ) -> impl Iterator<Item = &'a str> {
-    ["Default", "Vertical", "Groups"]
+    ["Default", "Vertical", "Groups", "Stats"]
         .into_iter()
         .filter(move |s| s.to_lowercase().starts_with(&partial.to_lowercase()))
 }
@@ -78,7 +99,7 @@ pub async fn deck_context_menu(
     ctx.defer().await?;
 
     tracing::info!(msg.content);
-    deck_inner(ctx, msg.content, None, None, None).await
+    deck_inner(ctx, msg.content, None, None, None, false).await
 }
 
 pub async fn deck_inner(
@@ -87,12 +108,14 @@ pub async fn deck_inner(
     title: Option<String>,
     format: Option<String>,
     shape: Option<String>,
+    archetype: bool,
 ) -> Result<(), Error> {
     let locale = get_server_locale(&ctx);
 
     let l_opts = LookupOptions::lookup(&code)
         .with_locale(locale)
-        .with_custom_format(format.as_deref());
+        .with_custom_format(format.as_deref())
+        .with_auto_title(archetype && title.is_none());
 
     let i_opts = match shape {
         Some(s) if s.starts_with('V') || s.starts_with('v') => deck::ImageOptions::Regular {
@@ -100,6 +123,9 @@ pub async fn deck_inner(
             inline_sideboard: true,
         },
         Some(s) if s.starts_with('g') || s.starts_with('G') => deck::ImageOptions::Groups,
+        Some(s) if s.starts_with('s') || s.starts_with('S') => {
+            deck::ImageOptions::WithStats { groups: false }
+        }
         _ => deck::ImageOptions::Adaptable,
     };
 
@@ -111,6 +137,110 @@ pub async fn deck_inner(
     send_deck_reply(ctx, deck, i_opts).await
 }
 
+/// Get deck list as text to paste into the game client
+#[poise::command(slash_command, install_context = "Guild|User", category = "Deck")]
+pub async fn deckexport(
+    ctx: Context<'_>,
+    #[description = "deck code"] code: String,
+    #[description = "title"] title: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let locale = get_server_locale(&ctx);
+
+    let mut deck = deck::lookup(LookupOptions::lookup(&code).with_locale(locale))?;
+    if let Some(title) = title {
+        deck.title = title.into();
+    }
+
+    ctx.say(format!("```\n{}\n```", deck.to_client_text(locale)))
+        .await?;
+
+    Ok(())
+}
+
+/// Chances to draw cards from a deck, or to curve out
+#[poise::command(slash_command, install_context = "Guild|User", category = "Deck")]
+pub async fn odds(
+    ctx: Context<'_>,
+    #[description = "deck code"] code: String,
+    #[description = "cards to look for, separated by commas. part of the name is enough"]
+    cards: Option<String>,
+    #[description = "show odds until this turn (default 4)"]
+    #[min = 1]
+    #[max = 10]
+    turn: Option<u8>,
+    #[description = "throw back the whole opening hand when it has none of the cards"]
+    mulligan: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let locale = get_server_locale(&ctx);
+    let deck = deck::lookup(LookupOptions::lookup(&code).with_locale(locale))?;
+
+    let turn = turn.unwrap_or(4);
+    let mulligan = if mulligan.unwrap_or_default() {
+        Mulligan::Hunt
+    } else {
+        Mulligan::Keep
+    };
+    let first = Odds::new(&deck).with_mulligan(mulligan);
+    let second = Odds::new(&deck).with_mulligan(mulligan).going_first(false);
+
+    let percent = |p: f64| format!("{:.1}%", p * 100.0);
+
+    let mut fields = vec![];
+
+    if let Some(cards) = cards {
+        let cards = cards
+            .split(',')
+            .map(str::trim)
+            .filter(|c| c.is_empty().not())
+            .collect::<Vec<_>>();
+
+        let mut table = format!("{:<8} {:>7} {:>7}", "Turn", "First", "Second");
+        for t in 0..=usize::from(turn) {
+            let label = if t == 0 {
+                String::from("Opening")
+            } else {
+                t.to_string()
+            };
+            write!(
+                table,
+                "\n{label:<8} {:>7} {:>7}",
+                percent(first.by_turn(&cards, t)?),
+                percent(second.by_turn(&cards, t)?)
+            )
+            .ok();
+        }
+
+        fields.push((
+            format!("{} copies of {}", first.copies(&cards)?, cards.join(", ")),
+            format!("```\n{table}\n```"),
+            false,
+        ));
+    }
+
+    fields.push((
+        format!("Curve out until turn {turn}"),
+        format!(
+            "{} going first, {} going second",
+            percent(first.curve_out(turn)),
+            percent(second.curve_out(turn))
+        ),
+        false,
+    ));
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("{} Odds", deck.title))
+        .color(deck.class.color())
+        .fields(fields);
+
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+
+    Ok(())
+}
+
 /// Compare two decks
 #[poise::command(slash_command, install_context = "Guild|User", category = "Deck")]
 pub async fn deckcomp(
@@ -172,14 +302,23 @@ async fn send_deck_reply(
     deck: Deck,
     opts: deck::ImageOptions,
 ) -> Result<(), Error> {
-    ctx.send(create_deck_reply(&deck, opts)?).await?;
+    ctx.send(create_deck_reply(&deck, opts, get_server_locale(&ctx))?)
+        .await?;
 
     Ok(())
 }
 
+fn deck_stats_field(
+    stats: &deck::DeckStats,
+    locale: Locale,
+) -> String {
+    format!("```\n{}```", stats.in_locale(locale))
+}
+
 fn create_deck_reply(
     deck: &Deck,
     opts: deck::ImageOptions,
+    locale: Locale,
 ) -> Result<poise::CreateReply, Error> {
     let attachment_name = format!(
         "{}.png",
@@ -206,8 +345,35 @@ fn create_deck_reply(
         ))
         .description(&*deck.deck_code)
         .color(deck.class.color())
+        .field("Dust", deck.dust_cost().total().to_string(), true)
+        .field("Stats", deck_stats_field(&deck.stats(), locale), false)
         .attachment(attachment_name);
 
+    let validation = deck::validate(deck);
+    if validation.violations.is_empty().not() {
+        embed = embed.field(
+            "Deck Issues",
+            validation
+                .violations
+                .iter()
+                .take(5)
+                .map(|v| format!("- {v}"))
+                .join("\n"),
+            false,
+        );
+    }
+    if validation.unchecked.is_empty().not() {
+        embed = embed.field(
+            "Unchecked",
+            format!(
+                "Could not check whether {} cards are legal in {}.",
+                validation.unchecked.len(),
+                deck.format
+            ),
+            false,
+        );
+    }
+
     if random::<u8>() % 10 == 0 {
         embed = embed.footer(serenity::CreateEmbedFooter::new(
             "See other useful commands with /help.",
@@ -227,6 +393,8 @@ pub async fn metadeck(
     ctx: Context<'_>,
     #[description = "Class"] class: Option<String>,
     #[description = "Format"] format: Option<String>,
+    #[description = "Rank bracket, e.g. legend, diamond, all"] rank: Option<String>,
+    #[description = "Time period, e.g. past-3, past-7, last-patch"] period: Option<String>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
@@ -234,8 +402,9 @@ pub async fn metadeck(
 
     let class = class.and_then(|s| s.parse().ok());
     let format = parse_format(ctx, format).await;
+    let query = meta_query(format, rank, period)?;
 
-    let deck = meta::meta_deck(class, format, locale)?
+    let deck = meta::meta_deck(class, query, locale)?
         .take(5)
         .find_or_first(|_| random::<u8>() % 5 == 0)
         .ok_or("no deck found")?;
@@ -243,17 +412,88 @@ pub async fn metadeck(
     send_deck_reply(ctx, deck, deck::ImageOptions::Adaptable).await
 }
 
+/// Find the meta decks closest to a deck
+#[poise::command(slash_command, install_context = "Guild|User", category = "Metagame")]
+pub async fn similar(
+    ctx: Context<'_>,
+    #[description = "deck code"] code: String,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let locale = get_server_locale(&ctx);
+
+    let deck = deck::lookup(LookupOptions::lookup(&code).with_locale(locale))?;
+    let similar = meta::similar_decks(&deck, 5, locale)?;
+    let best = similar.first().ok_or("no meta decks found for this deck")?;
+
+    let swaps = |map: &HashMap<card::Card, usize>, sign: char| {
+        map.iter()
+            .sorted()
+            .map(|(card, count)| format!("{sign} {count}x {}", card.name))
+            .join("\n")
+    };
+    let swaps = [
+        swaps(&best.swaps.deck1_uniques, '-'),
+        swaps(&best.swaps.deck2_uniques, '+'),
+    ]
+    .into_iter()
+    .filter(|s| s.is_empty().not())
+    .join("\n");
+
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Meta Decks Similar to {}", deck.title))
+        .url("https://go.overwolf.com/firestone-app/")
+        .color(deck.class.color())
+        .description(
+            similar
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    format!(
+                        "{}. {:.0}% similar: {}",
+                        i + 1,
+                        s.similarity * 100.0,
+                        s.deck.title
+                    )
+                })
+                .join("\n"),
+        )
+        .field(
+            "Swaps to the closest deck",
+            if swaps.len() > 1024 {
+                String::from("List longer than Discord limits.")
+            } else if swaps.is_empty() {
+                String::from("Same list.")
+            } else {
+                format!("```diff\n{swaps}\n```")
+            },
+            false,
+        );
+
+    let decks = similar
+        .into_iter()
+        .map(|s| s.deck)
+        .enumerate()
+        .collect::<Vec<_>>();
+
+    create_deck_dropdown(ctx, embed, &decks).await
+}
+
 /// Get a meta snapshot from Firestone's data.
 #[poise::command(slash_command, install_context = "Guild|User", category = "Metagame")]
 pub async fn metasnap(
     ctx: Context<'_>,
     #[description = "Format"] format: Option<String>,
+    #[description = "Rank bracket, e.g. legend, diamond, all"] rank: Option<String>,
+    #[description = "Time period, e.g. past-3, past-7, last-patch"] period: Option<String>,
 ) -> Result<(), Error> {
     ctx.defer().await?;
 
     let locale = get_server_locale(&ctx);
     let format = parse_format(ctx, format).await;
-    let decks = meta::meta_snap(format.clone(), locale)?
+    let query = meta_query(format.clone(), rank, period)?;
+    let (rank, period) = (query.rank(), query.period());
+    let decks = meta::meta_snap(query, locale)?
         .enumerate()
         .take(10)
         .collect::<Vec<_>>();
@@ -271,14 +511,132 @@ pub async fn metasnap(
                 .join("\n"),
         )
         .color(decks[0].1.class.color())
-        .footer(serenity::CreateEmbedFooter::new(
+        .footer(serenity::CreateEmbedFooter::new(format!(
             "Best performing deck of each archetype.\n\
-            Data is from the past 3 days, Diamond to Legend (usually).",
-        ));
+            Data is {}, {}.",
+            period_description(period),
+            rank.map_or_else(
+                || String::from("Diamond to Legend (usually)"),
+                |rank| rank.to_string()
+            )
+        )));
 
     create_deck_dropdown(ctx, embed, &decks).await
 }
 
+/// Get a table of every archetype from Firestone's data.
+#[poise::command(slash_command, install_context = "Guild|User", category = "Metagame")]
+pub async fn metatable(
+    ctx: Context<'_>,
+    #[description = "Format"] format: Option<String>,
+    #[description = "Rank bracket, e.g. legend, diamond, all"] rank: Option<String>,
+    #[description = "Time period, e.g. past-3, past-7, last-patch"] period: Option<String>,
+    #[description = "Column to sort by: games, winrate, class, lists or name"] sort: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let format = parse_format(ctx, format).await;
+    let title = format!(
+        "{} Archetypes (from Firestone)",
+        format.to_string().to_uppercase()
+    );
+    let query = meta_query(format, rank, period)?;
+    let sort = sort.map(|s| s.parse()).transpose()?.unwrap_or_default();
+
+    let archetypes = meta::meta_archetypes(query, sort)?;
+    let name_width = archetypes
+        .iter()
+        .map(|a| a.name.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let pages = archetypes
+        .into_iter()
+        .enumerate()
+        .chunks(15)
+        .into_iter()
+        .map(Iterator::collect::<Vec<_>>)
+        .collect::<Vec<_>>();
+
+    paginated_embeds(ctx, pages.into_iter(), |page| {
+        let rows = page
+            .iter()
+            .map(|(i, a)| {
+                format!(
+                    "{:>2}. {:<name_width$} {:>7} {:>5.1}% {:>5.1}%",
+                    i + 1,
+                    a.name,
+                    a.total_games,
+                    a.popularity * 100.0,
+                    a.winrate * 100.0
+                )
+            })
+            .join("\n");
+
+        serenity::CreateEmbed::new()
+            .title(&title)
+            .url("https://go.overwolf.com/firestone-app/")
+            .color(page[0].1.class.color())
+            .description(format!(
+                "```\n    {:<name_width$} {:>7} {:>6} {:>6}\n{rows}\n```",
+                "Archetype", "Games", "Pop.", "WR"
+            ))
+    })
+    .await
+}
+
+/// Get a tier list image of every archetype from Firestone's data.
+#[poise::command(slash_command, install_context = "Guild|User", category = "Metagame")]
+pub async fn tierlist(
+    ctx: Context<'_>,
+    #[description = "Format"] format: Option<String>,
+    #[description = "Rank bracket, e.g. legend, diamond, all"] rank: Option<String>,
+    #[description = "Time period, e.g. past-3, past-7, last-patch"] period: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let format = parse_format(ctx, format).await;
+    let query = meta_query(format, rank, period)?;
+    let list = meta::tier_list(query)?;
+
+    let attachment_name = format!("{}_tier_list.png", list.format);
+    let attachment = {
+        let img = list.get_image();
+
+        let mut image_data = Cursor::new(Vec::<u8>::new());
+        img.write_to(&mut image_data, image::ImageFormat::Png)?;
+
+        serenity::CreateAttachment::bytes(image_data.into_inner(), attachment_name.as_str())
+    };
+
+    let embed = serenity::CreateEmbed::new()
+        .title(format!(
+            "{} Tier List (from Firestone)",
+            list.format.to_string().to_uppercase()
+        ))
+        .url("https://go.overwolf.com/firestone-app/")
+        .color(
+            list.tiers
+                .iter()
+                .flatten()
+                .next()
+                .map_or((10, 10, 10), |a| a.class.color()),
+        )
+        .attachment(attachment_name)
+        .footer(serenity::CreateEmbedFooter::new(
+            "Archetypes by winrate over all their lists.",
+        ));
+
+    ctx.send(
+        poise::CreateReply::default()
+            .attachment(attachment)
+            .embed(embed),
+    )
+    .await?;
+
+    Ok(())
+}
+
 /// Find a deck with archetype name
 #[poise::command(slash_command, install_context = "Guild|User", category = "Metagame")]
 pub async fn archetype(
@@ -326,11 +684,12 @@ async fn create_deck_dropdown(
 
     let mut list_handle = None::<poise::ReplyHandle<'_>>;
 
+    let locale = get_server_locale(&ctx);
     let replies = decks
         .iter()
         .map(|(_, deck)| {
             LazyCell::new(|| {
-                create_deck_reply(deck, deck::ImageOptions::Adaptable).unwrap_or_default()
+                create_deck_reply(deck, deck::ImageOptions::Adaptable, locale).unwrap_or_default()
             })
         })
         .collect::<Vec<_>>();
@@ -374,6 +733,28 @@ async fn create_deck_dropdown(
     Ok(())
 }
 
+fn meta_query(
+    format: deck::Format,
+    rank: Option<String>,
+    period: Option<String>,
+) -> Result<meta::MetaQuery, Error> {
+    let rank = rank.map(|s| s.parse()).transpose()?;
+    let period = period.map(|s| s.parse()).transpose()?.unwrap_or_default();
+    Ok(meta::MetaQuery::new(format)
+        .with_rank(rank)
+        .with_period(period))
+}
+
+const fn period_description(period: meta::TimePeriod) -> &'static str {
+    match period {
+        meta::TimePeriod::Past3Days => "from the past 3 days",
+        meta::TimePeriod::Past7Days => "from the past 7 days",
+        meta::TimePeriod::Past20Days => "from the past 20 days",
+        meta::TimePeriod::CurrentSeason => "from the current season",
+        meta::TimePeriod::CurrentPatch => "since the last patch",
+    }
+}
+
 async fn parse_format(
     ctx: Context<'_>,
     format: Option<String>,
@@ -43,9 +43,14 @@ async fn poise(#[shuttle_runtime::Secrets] secret_store: SecretStore) -> Shuttle
                 deck_cmds::code(),
                 deck_cmds::deck_context_menu(),
                 deck_cmds::deckcomp(),
+                deck_cmds::deckexport(),
+                deck_cmds::odds(),
                 deck_cmds::archetype(),
                 deck_cmds::metadeck(),
                 deck_cmds::metasnap(),
+                deck_cmds::metatable(),
+                deck_cmds::tierlist(),
+                deck_cmds::similar(),
                 helpers::news(),
                 helpers::patchtime(),
                 helpers::help(),
@@ -4,6 +4,7 @@ use mimiron::{
     bg,
     localization::{Locale, Localize},
 };
+use std::ops::Not;
 
 #[derive(Args)]
 #[command(group = ArgGroup::new("search").required(true).multiple(true))]
@@ -24,6 +25,10 @@ pub struct BGArgs {
     #[arg(long)]
     text: bool,
 
+    /// Restrict the search to a pool. Hero stats are shown for Solos and Duos unless one is chosen
+    #[arg(short, long, default_value("all"))]
+    pool: bg::Pool,
+
     /// Print image links.
     #[arg(short, long)]
     image: bool,
@@ -45,12 +50,39 @@ pub fn run(
                 .and_then(|s| s.parse().inspect_err(|e| eprintln!("{e}")).ok()),
         )
         .with_text(args.text)
+        .for_pool(args.pool)
         .debug(args.debug);
 
     let cards = bg::lookup(opts)?;
 
+    let pools = match args.pool {
+        bg::Pool::All => vec![("Solos", bg::Pool::Solos), ("Duos", bg::Pool::Duos)],
+        bg::Pool::Solos => vec![("Solos", bg::Pool::Solos)],
+        bg::Pool::Duos => vec![("Duos", bg::Pool::Duos)],
+    };
+    let mut hero_stats = None;
+
     for card in cards {
         println!("{:#}", card.in_locale(locale));
+        if matches!(card.card_type, bg::BGCardType::Hero { .. }) {
+            // Fetched with the first hero only, so other searches don't wait on Firestone.
+            let hero_stats = hero_stats.get_or_insert_with(|| {
+                pools
+                    .iter()
+                    .filter_map(|&(name, pool)| {
+                        let stats = bg::hero_stats(pool)
+                            .inspect_err(|e| eprintln!("{e}"))
+                            .ok()?;
+                        Some((name, stats))
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for (name, stats) in hero_stats.iter() {
+                if let Some(stats) = stats.get(&card.id) {
+                    println!("\t{name}: {}", stats.in_locale(locale));
+                }
+            }
+        }
         if args.image {
             println!("\tImage: {}", card.image);
         }
@@ -59,5 +91,20 @@ pub fn run(
         }
     }
 
+    // Compositions are not tied to a hero, so they are printed once after all heroes.
+    if hero_stats.is_some() {
+        for &(name, pool) in &pools {
+            let Ok(comps) = bg::comp_stats(pool).inspect_err(|e| eprintln!("{e}")) else {
+                continue;
+            };
+            if comps.is_empty().not() {
+                println!("{name} top compositions:");
+                for comp in comps.iter().take(5) {
+                    println!("\t{}", comp.in_locale(locale));
+                }
+            }
+        }
+    }
+
     Ok(())
 }
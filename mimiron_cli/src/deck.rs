@@ -1,13 +1,15 @@
 use anyhow::Result;
 use clap::{Args, ValueEnum};
 use mimiron::{
-    deck::{self, LookupOptions},
+    deck::{self, Export, ImportOptions, LookupOptions},
     localization::{Locale, Localize},
+    meta,
 };
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use std::{
     fs::File,
     io::{BufRead, BufReader},
+    ops::Not,
     path::{Path, PathBuf},
 };
 
@@ -16,9 +18,14 @@ pub struct DeckArgs {
     /// Deck code to parse
     input: String,
 
-    /// Compare with a second deck
-    #[arg(short, long, value_name("DECK2"))]
-    comp: Option<String>,
+    /// Compare with a second deck.
+    ///
+    /// With more than one code, compares all the decks together: the cards they all run, how
+    /// often the other cards show up, and the consensus list.
+    ///
+    /// --image saves the comparison, or the consensus list when comparing more than two decks.
+    #[arg(short, long, value_name("DECKS"), num_args(1..))]
+    comp: Vec<String>,
 
     /// Instead of a code, specify a file with multiple deck codes (separated by new lines).
     ///
@@ -27,12 +34,52 @@ pub struct DeckArgs {
     #[arg(long, conflicts_with("comp"))]
     batch: bool,
 
+    /// Instead of a code, specify a file with a card list for the given class.
+    ///
+    /// One card per line, like "2x Fireball", "1 Ragnaros the Firelord", or "(3) Frostbolt x2".
+    #[arg(long, value_name("CLASS"), conflicts_with("batch"))]
+    list: Option<String>,
+
     /// Override format/game mode provided by code (For Twist, Tavern Brawl, etc.)
     #[arg(short, long)]
     mode: Option<String>,
 
+    /// Use data from hearthstonejson.com only. Does not need Blizzard credentials. English only.
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Name the deck after its closest meta archetype, unless the input has a title.
+    #[arg(long, conflicts_with("offline"))]
+    name_archetype: bool,
+
+    /// Print the deck as the game client copies it, ready to paste back into the game
+    #[arg(long, conflicts_with("comp"))]
+    export: bool,
+
+    /// Print the deck (or the comparison) in a machine readable format instead.
+    ///
+    /// When comparing more than two decks, prints the consensus list.
+    #[arg(long, value_name("FORMAT"), conflicts_with("export"))]
+    output_format: Option<OutputFormat>,
+
+    /// Show deck statistics: mana curve, card types, spell schools, minion types, and runes.
+    ///
+    /// With --image, adds a stats panel to the image. Only the Groups and Adapt formats are
+    /// supported, and other formats fall back to Adapt.
+    #[arg(long, conflicts_with("comp"))]
+    stats: bool,
+
+    /// Show how each card does in the deck's archetype: mulligan, kept, drawn and played
+    /// winrates from Firestone.
+    #[arg(long, conflicts_with_all(["comp", "offline"]))]
+    card_stats: bool,
+
+    /// The archetype for --card-stats. Guessed from the deck if not set
+    #[arg(long, requires("card_stats"))]
+    archetype: Option<String>,
+
     /// Save deck image. Defaults to Downloads folder unless --output is set
-    #[arg(short, long, conflicts_with("comp"))]
+    #[arg(short, long)]
     image: bool,
 
     /// Choose where to save the deck image
@@ -56,6 +103,26 @@ pub struct DeckArgs {
     format: ImageFormat,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Md,
+}
+impl OutputFormat {
+    fn export(
+        self,
+        item: &impl Export,
+        locale: Locale,
+    ) -> String {
+        match self {
+            Self::Json => item.to_json(),
+            Self::Csv => item.to_csv(),
+            Self::Md => item.to_markdown(locale),
+        }
+    }
+}
+
 #[derive(Clone, ValueEnum)]
 enum ImageFormat {
     Groups,
@@ -105,23 +172,89 @@ pub fn run_one(
     args: DeckArgs,
     locale: Locale,
 ) -> Result<()> {
-    let opts = LookupOptions::lookup(&args.input)
-        .with_locale(locale)
-        .with_custom_format(args.mode.as_deref());
+    let deck = get_deck(&args, locale)?;
+
+    let deck = if args.card_stats {
+        with_card_stats(deck, args.archetype.as_deref())?
+    } else {
+        deck
+    };
 
-    let deck = deck::lookup(opts)?;
+    let others = args
+        .comp
+        .iter()
+        .map(|code| {
+            deck::lookup(
+                LookupOptions::lookup(code)
+                    .with_locale(locale)
+                    .offline(args.offline),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     // Deck compare and/or printing
-    if let Some(code) = args.comp {
-        let deck2 = deck::lookup(LookupOptions::lookup(&code).with_locale(locale))?;
-        let deck_diff = deck.compare_with(&deck2);
-        println!("{}", deck_diff.in_locale(locale));
+    if let [deck2] = others.as_slice() {
+        let deck_diff = deck.compare_with(deck2);
+        match args.output_format {
+            Some(output_format) => println!("{}", output_format.export(&deck_diff, locale)),
+            None => println!("{}", deck_diff.in_locale(locale)),
+        }
+
+        if args.image {
+            let img = deck_diff.get_image(&deck, deck2);
+            img.save(image_path(&deck, args.output))?;
+        }
+
+        return Ok(());
+    } else if others.is_empty().not() {
+        let decks = std::iter::once(deck).chain(others).collect::<Vec<_>>();
+        let comparison = deck::compare_decks(&decks, locale)?;
+        match args.output_format {
+            Some(output_format) => {
+                println!("{}", output_format.export(&comparison.consensus, locale));
+            }
+            None => println!("{}", comparison.in_locale(locale)),
+        }
+
+        if args.image {
+            let img = comparison.get_image();
+            img.save(image_path(&comparison.consensus, args.output))?;
+        }
+
+        return Ok(());
+    } else if let Some(output_format) = args.output_format {
+        println!("{}", output_format.export(&deck, locale));
+    } else if args.export {
+        println!("{}", deck.to_client_text(locale));
     } else {
         println!("{}", deck.in_locale(locale));
+        println!("{}", deck.dust_cost().in_locale(locale));
+
+        if args.stats {
+            println!("\n{}", deck.stats().in_locale(locale));
+        }
+
+        let validation = deck::validate(&deck);
+        if validation.violations.is_empty().not() {
+            println!("\nDeck issues:");
+            for violation in validation.violations {
+                println!("\t{violation}");
+            }
+        }
+        if validation.unchecked.is_empty().not() {
+            println!(
+                "\nCould not check whether these are legal in {}: {}",
+                deck.format,
+                validation.unchecked.join(", ")
+            );
+        }
     }
 
     if args.image {
         let opts = match args.format {
+            _ if args.stats => deck::ImageOptions::WithStats {
+                groups: matches!(args.format, ImageFormat::Groups),
+            },
             ImageFormat::Groups => deck::ImageOptions::Groups,
             ImageFormat::Adapt => deck::ImageOptions::Adaptable,
             ImageFormat::Single => deck::ImageOptions::Regular {
@@ -139,30 +272,82 @@ pub fn run_one(
         };
 
         let img = deck.get_image(opts);
-
-        let file_name = format!(
-            "{} {} {}.png",
-            deck.title,
-            deck.deck_code
-                .chars()
-                .filter(|c| c.is_alphanumeric())
-                .collect::<String>(),
-            jiff::Zoned::now().strftime("%Y%m%d %H%M")
-        );
-
-        let save_file = args
-            .output
-            .unwrap_or_else(|| {
-                directories::UserDirs::new()
-                    .expect("couldn't get user directories")
-                    .download_dir()
-                    .expect("couldn't get downloads directory")
-                    .to_path_buf()
-            })
-            .join(file_name);
-
-        img.save(save_file)?;
+        img.save(image_path(&deck, args.output))?;
     }
 
     Ok(())
 }
+
+// From a deck code, or from a card list with --list.
+fn get_deck(
+    args: &DeckArgs,
+    locale: Locale,
+) -> Result<deck::Deck> {
+    if let Some(class) = &args.list {
+        let list = std::fs::read_to_string(&args.input)?;
+        let opts = ImportOptions::import(&list, class.parse()?)
+            .with_locale(locale)
+            .with_format(args.mode.as_deref());
+
+        let (deck, issues) = deck::import(opts)?;
+        for issue in issues {
+            eprintln!("{issue}");
+        }
+        Ok(deck)
+    } else {
+        let opts = LookupOptions::lookup(&args.input)
+            .with_locale(locale)
+            .with_custom_format(args.mode.as_deref())
+            .offline(args.offline)
+            .with_auto_title(args.name_archetype);
+
+        deck::lookup(opts)
+    }
+}
+
+fn with_card_stats(
+    deck: deck::Deck,
+    archetype: Option<&str>,
+) -> Result<deck::Deck> {
+    let archetype = match archetype {
+        Some(archetype) => archetype.into(),
+        None => meta::classify(&deck)?
+            .into_iter()
+            .next()
+            .map(|(archetype, _)| archetype)
+            .ok_or_else(|| anyhow::anyhow!("Could not tell the deck's archetype."))?,
+    };
+
+    let stats = meta::card_stats(&archetype, deck.format.clone())?;
+    eprintln!("Card stats of {archetype}.");
+
+    Ok(deck.with_card_stats(stats))
+}
+
+fn image_path(
+    deck: &deck::Deck,
+    output: Option<PathBuf>,
+) -> PathBuf {
+    let file_name = format!(
+        "{} {} {}.png",
+        deck.title,
+        deck.deck_code
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>(),
+        jiff::Zoned::now().strftime("%Y%m%d %H%M")
+    );
+
+    output_dir(output).join(file_name)
+}
+
+/// `output`, or the Downloads folder.
+pub fn output_dir(output: Option<PathBuf>) -> PathBuf {
+    output.unwrap_or_else(|| {
+        directories::UserDirs::new()
+            .expect("couldn't get user directories")
+            .download_dir()
+            .expect("couldn't get downloads directory")
+            .to_path_buf()
+    })
+}
@@ -6,6 +6,7 @@ mod bg;
 mod card;
 mod deck;
 mod meta;
+mod odds;
 
 #[derive(Parser)]
 #[command(author, version)]
@@ -14,10 +15,10 @@ struct Cli {
     locale: Locale,
 
     #[arg(env(mimiron::BLIZZARD_CLIENT_ID), hide_env_values(true))]
-    id: String,
+    id: Option<String>,
 
     #[arg(env(mimiron::BLIZZARD_CLIENT_SECRET), hide_env_values(true))]
-    secret: String,
+    secret: Option<String>,
 
     #[command(subcommand)]
     command: Commands,
@@ -36,6 +37,13 @@ enum Commands {
     /// Deck codes must be _only_ the deck code. The long code you get straight from Hearthstone's copy deck button is not usable.
     Deck(deck::DeckArgs),
 
+    /// Chances to draw cards from a deck code, or to curve out.
+    Odds(odds::OddsArgs),
+
+    /// Simulate the opening turns of a deck code many times, with a mulligan policy.
+    #[command(alias("sim"))]
+    Simulate(odds::SimulateArgs),
+
     /// Search for a Battlegrounds card by name
     ///
     /// Make sure the card's name is surrounded by quotation marks if it includes spaces or non-letter characters.
@@ -45,6 +53,9 @@ enum Commands {
     #[clap(hide = true)]
     Token,
 
+    /// Find the meta decks closest to a deck code, and the swaps to turn it into the closest one
+    Similar(meta::SimilarArgs),
+
     /// Get a meta deck from Firestone's data
     #[clap(hide = true)]
     Meta(meta::MetaArgs),
@@ -71,13 +82,26 @@ pub fn run() -> Result<()> {
     let args = Cli::parse();
     let locale = args.locale;
 
-    mimiron::set_blizzard_client_auth(args.id, args.secret);
+    match (args.id, args.secret) {
+        (Some(id), Some(secret)) => mimiron::set_blizzard_client_auth(id, secret),
+        _ if matches!(&args.command, Commands::Deck(deck) if deck.offline) => {}
+        _ if matches!(&args.command, Commands::Odds(odds) if odds.offline) => {}
+        _ if matches!(&args.command, Commands::Simulate(sim) if sim.offline) => {}
+        _ => anyhow::bail!(
+            "{} and {} are required unless using offline mode.",
+            mimiron::BLIZZARD_CLIENT_ID,
+            mimiron::BLIZZARD_CLIENT_SECRET
+        ),
+    }
 
     match args.command {
         Commands::Card(args) => card::run(args, locale)?,
         Commands::Deck(args) => deck::run(args, locale)?,
+        Commands::Odds(args) => odds::run(args, locale)?,
+        Commands::Simulate(args) => odds::simulate(args, locale)?,
         Commands::BG(args) => bg::run(args, locale)?,
         Commands::Meta(args) => meta::run(args, locale)?,
+        Commands::Similar(args) => meta::similar(args, locale)?,
 
         Commands::Token => println!("{}", mimiron::get_access_token()),
 
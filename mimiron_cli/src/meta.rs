@@ -1,27 +1,362 @@
 use anyhow::Result;
-use clap::Args;
+use clap::{Args, Subcommand};
 use mimiron::{
     card_details::Class,
-    deck::Format,
+    deck::{self, Format, LookupOptions},
     localization::{Locale, Localize},
-    meta::meta_deck,
+    meta::{
+        ArchetypeSort, ArchetypeStats, MetaQuery, Mover, RankBracket, Since, SnapshotStore,
+        TimePeriod, matchup, matchup_matrix, meta_archetypes, meta_deck, meta_snap, similar_decks,
+        tier_list,
+    },
 };
+use std::path::PathBuf;
 
 #[derive(Args, Clone)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct MetaArgs {
-    class: Class,
+    #[command(subcommand)]
+    command: Option<MetaCommands>,
+
+    #[arg(required = true)]
+    class: Option<Class>,
+
+    #[command(flatten)]
+    query: QueryArgs,
+}
+
+#[derive(Subcommand, Clone)]
+enum MetaCommands {
+    /// The best deck of each archetype, or a table of all archetypes
+    Snap {
+        #[command(flatten)]
+        query: QueryArgs,
+
+        /// One row per archetype, with all its lists counted together
+        #[arg(short, long)]
+        table: bool,
+
+        /// Sort the table by games, popularity, winrate, class, lists or name
+        #[arg(short, long, default_value("games"), requires("table"))]
+        sort: ArchetypeSort,
+
+        /// Save a tier list image of all archetypes. Defaults to Downloads folder unless
+        /// --output is set
+        #[arg(short, long, conflicts_with("table"))]
+        image: bool,
+
+        /// Choose where to save the image
+        #[arg(short, long, requires("image"))]
+        output: Option<PathBuf>,
+    },
+
+    /// Winrates between the most played archetypes
+    Matchups {
+        #[command(flatten)]
+        query: QueryArgs,
+
+        /// Number of archetypes
+        #[arg(short = 'n', long, default_value("10"))]
+        size: usize,
+
+        /// Only show how the first archetype fares against the second. Part of the names is enough
+        #[arg(long, num_args(2), value_names(["ARCHETYPE", "OPPONENT"]), conflicts_with("image"))]
+        vs: Vec<String>,
+
+        /// Save a heatmap image. Defaults to Downloads folder unless --output is set
+        #[arg(short, long)]
+        image: bool,
+
+        /// Choose where to save the image
+        #[arg(short, long, requires("image"))]
+        output: Option<PathBuf>,
+    },
+
+    /// Save daily snapshots of the meta, and compare them over time
+    History {
+        #[command(subcommand)]
+        command: HistoryCommands,
+
+        /// Where snapshots are kept. Defaults to mimiron's data folder
+        #[arg(long, global = true)]
+        dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum HistoryCommands {
+    /// Save today's snapshot. Run it once a day to build up history
+    Save {
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+
+    /// Winrate and popularity of an archetype on every saved day
+    Trend {
+        /// Part of the name is enough
+        archetype: String,
+
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+
+    /// Archetypes whose winrate rose or fell the most
+    Movers {
+        #[command(flatten)]
+        query: QueryArgs,
+
+        /// yesterday, last-patch, a number of days, or a date like 2025-01-31
+        #[arg(short, long, default_value("yesterday"))]
+        since: Since,
+
+        /// Number of risers and of fallers
+        #[arg(short = 'n', long, default_value("5"))]
+        count: usize,
+
+        /// Rank by popularity change instead
+        #[arg(long)]
+        popularity: bool,
+    },
+}
+
+#[derive(Args, Clone)]
+struct QueryArgs {
     #[arg(default_value = "standard")]
     format: Format,
+
+    /// Rank bracket, such as legend, diamond or all. Defaults to Legend and Diamond, or all
+    /// ranks if there are not enough games
+    #[arg(short, long)]
+    rank: Option<RankBracket>,
+
+    /// past-3, past-7, past-20, current-season or last-patch
+    #[arg(short, long, default_value("past-3"))]
+    period: TimePeriod,
+
+    /// Ignore decks with fewer games
+    #[arg(long)]
+    min_games: Option<u32>,
+}
+
+impl From<QueryArgs> for MetaQuery {
+    fn from(args: QueryArgs) -> Self {
+        Self::new(args.format)
+            .with_rank(args.rank)
+            .with_period(args.period)
+            .with_min_games(args.min_games)
+    }
 }
 
 pub fn run(
     args: MetaArgs,
     locale: Locale,
 ) -> Result<()> {
-    let decks = meta_deck(Some(args.class), args.format, locale)?;
+    match args.command {
+        Some(MetaCommands::Snap {
+            query,
+            table: true,
+            sort,
+            ..
+        }) => print_table(&meta_archetypes(query, sort)?, locale),
+        Some(MetaCommands::Snap {
+            query,
+            image: true,
+            output,
+            ..
+        }) => {
+            let list = tier_list(query)?;
+            let file_name = format!(
+                "{} Tier List {}.png",
+                list.format.to_string().to_uppercase(),
+                jiff::Zoned::now().strftime("%Y%m%d %H%M")
+            );
+            list.get_image()
+                .save(crate::deck::output_dir(output).join(file_name))?;
+        }
+        Some(MetaCommands::Snap { query, .. }) => {
+            for (i, deck) in meta_snap(query, locale)?.enumerate().take(10) {
+                println!("{}. {}\n{}", i + 1, deck.title, deck.deck_code);
+            }
+        }
+        Some(MetaCommands::Matchups { query, vs, .. }) if vs.len() == 2 => {
+            let m = matchup(&vs[0], &vs[1], query)?;
+            println!(
+                "{:.1}% winrate over {} games.",
+                m.winrate * 100.0,
+                m.total_games
+            );
+        }
+        Some(MetaCommands::Matchups {
+            query,
+            size,
+            image,
+            output,
+            ..
+        }) => {
+            let file_name = format!(
+                "{} Matchups {}.png",
+                query.format.to_string().to_uppercase(),
+                jiff::Zoned::now().strftime("%Y%m%d %H%M")
+            );
+            let matrix = matchup_matrix(query, size)?;
+
+            if image {
+                matrix
+                    .get_image()
+                    .save(crate::deck::output_dir(output).join(file_name))?;
+            } else {
+                println!("{}", matrix.in_locale(locale));
+            }
+        }
+        Some(MetaCommands::History { command, dir }) => history(command, dir)?,
+        None => {
+            let decks = meta_deck(args.class, args.query, locale)?;
+            for deck in decks.take(3) {
+                println!("{}", deck.in_locale(locale));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn history(
+    command: HistoryCommands,
+    dir: Option<PathBuf>,
+) -> Result<()> {
+    let dir = dir
+        .or_else(|| {
+            directories::ProjectDirs::from("", "", "mimiron")
+                .map(|dirs| dirs.data_dir().join("snapshots"))
+        })
+        .ok_or_else(|| anyhow::anyhow!("Couldn't find a folder for snapshots. Set --dir."))?;
+    let store = SnapshotStore::new(dir);
+
+    match command {
+        HistoryCommands::Save { query } => {
+            let date = store.save(&query.into())?;
+            println!("Saved the snapshot of {date}.");
+        }
+        HistoryCommands::Trend { archetype, query } => {
+            println!(
+                "{:<10}  {:>8}  {:>10}  {:>7}",
+                "Date", "Games", "Popularity", "Winrate"
+            );
+            for point in store.archetype_trend(&archetype, &query.into())? {
+                println!(
+                    "{:<10}  {:>8}  {:>9.1}%  {:>6.1}%",
+                    point.date,
+                    point.total_games,
+                    point.popularity * 100.0,
+                    point.winrate * 100.0
+                );
+            }
+        }
+        HistoryCommands::Movers {
+            query,
+            since,
+            count,
+            popularity,
+        } => {
+            let changes = store.movers(&query.into(), since)?;
+            let mut movers = changes.movers;
+            let change = |m: &Mover| {
+                if popularity {
+                    m.popularity_change
+                } else {
+                    m.winrate_change
+                }
+            };
+            movers.sort_by(|m1, m2| change(m2).total_cmp(&change(m1)));
+
+            println!("From {} to {}.", changes.from, changes.to);
+            let print = |m: &Mover| {
+                println!(
+                    "{:>+6.1} {:>+6.1}  {} ({:.1}% winrate, {:.1}% popularity)",
+                    m.winrate_change * 100.0,
+                    m.popularity_change * 100.0,
+                    m.name,
+                    m.winrate * 100.0,
+                    m.popularity * 100.0
+                );
+            };
+
+            println!("\nRisers (winrate, popularity):");
+            movers
+                .iter()
+                .take_while(|m| change(m) > 0.0)
+                .take(count)
+                .for_each(print);
+            println!("\nFallers (winrate, popularity):");
+            movers
+                .iter()
+                .rev()
+                .take_while(|m| change(m) < 0.0)
+                .take(count)
+                .for_each(print);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(
+    archetypes: &[ArchetypeStats],
+    locale: Locale,
+) {
+    let name_width = archetypes
+        .iter()
+        .map(|a| a.name.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max("Archetype".len());
+
+    println!(
+        "{:<name_width$}  {:<12}  {:>8}  {:>10}  {:>7}  {:>5}",
+        "Archetype", "Class", "Games", "Popularity", "Winrate", "Lists"
+    );
+    for a in archetypes {
+        println!(
+            "{:<name_width$}  {:<12}  {:>8}  {:>9.1}%  {:>6.1}%  {:>5}",
+            a.name,
+            a.class.in_locale(locale).to_string(),
+            a.total_games,
+            a.popularity * 100.0,
+            a.winrate * 100.0,
+            a.lists
+        );
+    }
+}
+
+#[derive(Args)]
+pub struct SimilarArgs {
+    /// Deck code
+    code: String,
+
+    /// Number of meta decks to show
+    #[arg(short, long, default_value("5"))]
+    count: usize,
+}
+
+pub fn similar(
+    args: SimilarArgs,
+    locale: Locale,
+) -> Result<()> {
+    let deck = deck::lookup(LookupOptions::lookup(&args.code).with_locale(locale))?;
+    let similar = similar_decks(&deck, args.count, locale)?;
+
+    let Some((best, rest)) = similar.split_first() else {
+        anyhow::bail!("No meta decks found for this deck's class and format.");
+    };
 
-    for deck in decks.take(3) {
-        println!("{}", deck.in_locale(locale));
+    println!("{}", best.in_locale(locale));
+    for other in rest {
+        println!(
+            "{:.0}% similar: {}\n{}",
+            other.similarity * 100.0,
+            other.deck.title,
+            other.deck.deck_code
+        );
     }
 
     Ok(())
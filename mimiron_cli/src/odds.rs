@@ -0,0 +1,154 @@
+use anyhow::Result;
+use clap::{ArgGroup, Args};
+use mimiron::{
+    deck::{
+        self, LookupOptions,
+        odds::{self, Mulligan, MulliganPolicy, Odds, SimulationOptions},
+    },
+    localization::{Locale, Localize},
+};
+use std::ops::Not;
+
+#[derive(Args)]
+#[command(group = ArgGroup::new("odds").required(true).multiple(true))]
+pub struct OddsArgs {
+    /// Deck code
+    code: String,
+
+    /// Cards to look for. Part of the name is enough. Copies of all given cards count together
+    #[arg(short, long, num_args(1..), group = "odds")]
+    cards: Vec<String>,
+
+    /// Show odds until this turn
+    #[arg(short, long, default_value("4"))]
+    turn: u8,
+
+    /// Throw back the whole opening hand when it has none of the cards
+    #[arg(short, long)]
+    mulligan: bool,
+
+    /// Chance to play a card costing 1 on turn 1, 2 on turn 2, and so on until --turn
+    #[arg(long, group = "odds")]
+    curve: bool,
+
+    /// Use data from hearthstonejson.com only. Does not need Blizzard credentials. English only.
+    #[arg(long)]
+    pub offline: bool,
+}
+
+pub fn run(
+    args: OddsArgs,
+    locale: Locale,
+) -> Result<()> {
+    let deck = deck::lookup(
+        LookupOptions::lookup(&args.code)
+            .with_locale(locale)
+            .offline(args.offline),
+    )?;
+
+    let mulligan = if args.mulligan {
+        Mulligan::Hunt
+    } else {
+        Mulligan::Keep
+    };
+    let first = Odds::new(&deck).with_mulligan(mulligan);
+    let second = Odds::new(&deck).with_mulligan(mulligan).going_first(false);
+
+    let percent = |p: f64| format!("{:>6.1}%", p * 100.0);
+
+    if args.cards.is_empty().not() {
+        let cards = args.cards.iter().map(String::as_str).collect::<Vec<_>>();
+        println!(
+            "{} copies in {} cards.",
+            first.copies(&cards)?,
+            deck.cards.len()
+        );
+
+        println!("{:<8} {:>7} {:>7}", "Turn", "First", "Second");
+        for turn in 0..=usize::from(args.turn) {
+            let label = if turn == 0 {
+                String::from("Opening")
+            } else {
+                turn.to_string()
+            };
+            println!(
+                "{label:<8} {} {}",
+                percent(first.by_turn(&cards, turn)?),
+                percent(second.by_turn(&cards, turn)?)
+            );
+        }
+    }
+
+    if args.curve {
+        println!(
+            "\nCurve out until turn {}: {} going first, {} going second.",
+            args.turn,
+            percent(first.curve_out(args.turn)).trim(),
+            percent(second.curve_out(args.turn)).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    /// Deck code
+    code: String,
+
+    /// Keep cards costing this much or less in the mulligan
+    #[arg(long, default_value("2"), conflicts_with("keep"))]
+    keep_cost: u8,
+
+    /// Keep only these cards in the mulligan. Part of the name is enough
+    #[arg(short, long, num_args(1..))]
+    keep: Vec<String>,
+
+    /// Cards to report how often they are held. Part of the name is enough
+    #[arg(short, long, num_args(1..))]
+    cards: Vec<String>,
+
+    /// Simulate going second, with the Coin
+    #[arg(long)]
+    second: bool,
+
+    /// Number of games to simulate
+    #[arg(short, long, default_value("10000"))]
+    runs: usize,
+
+    /// The same seed always gives the same results
+    #[arg(short, long, default_value("0"))]
+    seed: u64,
+
+    /// Use data from hearthstonejson.com only. Does not need Blizzard credentials. English only.
+    #[arg(long)]
+    pub offline: bool,
+}
+
+pub fn simulate(
+    args: SimulateArgs,
+    locale: Locale,
+) -> Result<()> {
+    let deck = deck::lookup(
+        LookupOptions::lookup(&args.code)
+            .with_locale(locale)
+            .offline(args.offline),
+    )?;
+
+    let policy = if args.keep.is_empty() {
+        MulliganPolicy::MaxCost(args.keep_cost)
+    } else {
+        MulliganPolicy::KeepList(args.keep.into_iter().map(Into::into).collect())
+    };
+
+    let opts = SimulationOptions::simulate(&deck)
+        .with_policy(policy)
+        .tracking(args.cards.into_iter().map(Into::into).collect())
+        .going_first(args.second.not())
+        .with_runs(args.runs)
+        .with_seed(args.seed);
+
+    println!("{}", odds::simulate(opts).in_locale(locale));
+
+    Ok(())
+}